@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TileTextureIndex;
 use std::time::Duration;
 
 /// Stores animation information.
@@ -62,3 +63,96 @@ pub(crate) fn execute_animations(
         }
     }
 }
+
+/// A frame of a tile animation.
+#[derive(Debug, Clone)]
+pub(crate) struct TileFrame {
+    /// The index of the frame in the tilemap's texture (or the sprite's atlas), if it has one.
+    pub(crate) texture_index: Option<u32>,
+
+    /// The image of the frame, for tiles from image collections drawn as sprites without an atlas.
+    pub(crate) image: Option<Handle<Image>>,
+
+    pub(crate) duration: Duration,
+}
+
+/// Stores the frames of a tile animation, where each frame is displayed for its own duration.
+#[derive(Component, Debug)]
+pub(crate) struct TileAnimation {
+    frames: Vec<TileFrame>,
+    current_frame: usize,
+    frame_elapsed: Duration,
+}
+
+impl TileAnimation {
+    /// Creates a new tile animation from a list of frames.
+    ///
+    /// Frames with no duration are skipped, and `None` is returned if there is nothing left to
+    /// animate.
+    pub(crate) fn new(frames: impl IntoIterator<Item = TileFrame>) -> Option<Self> {
+        let frames: Vec<_> = frames
+            .into_iter()
+            .filter(|frame| !frame.duration.is_zero())
+            .collect();
+        if frames.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            frames,
+            current_frame: 0,
+            frame_elapsed: Duration::ZERO,
+        })
+    }
+
+    /// The frame currently being displayed.
+    pub(crate) fn current_frame(&self) -> &TileFrame {
+        &self.frames[self.current_frame]
+    }
+}
+
+// This system advances each animated tile through the frames of its `TileAnimation`, displaying
+// each frame for its own duration. Tiles on the tile grid have their `TileTextureIndex` updated,
+// while tiles drawn as sprites have their atlas index (or image, without an atlas) updated.
+pub(crate) fn execute_tile_animations(
+    time: Res<Time>,
+    mut animated_tiles: Query<(
+        &mut TileAnimation,
+        Option<&mut TileTextureIndex>,
+        Option<&mut Sprite>,
+    )>,
+) {
+    for (mut animation, texture_index, sprite) in &mut animated_tiles {
+        animation.frame_elapsed += time.delta();
+
+        // A long frame time may skip over multiple short frames.
+        loop {
+            let duration = animation.current_frame().duration;
+            if animation.frame_elapsed < duration {
+                break;
+            }
+            animation.frame_elapsed -= duration;
+            animation.current_frame = (animation.current_frame + 1) % animation.frames.len();
+        }
+
+        let frame = animation.current_frame();
+        if let (Some(mut texture_index), Some(index)) = (texture_index, frame.texture_index) {
+            if texture_index.0 != index {
+                texture_index.0 = index;
+            }
+        }
+        if let Some(mut sprite) = sprite {
+            match (&sprite.texture_atlas, frame.texture_index, &frame.image) {
+                (Some(atlas), Some(index), _) if atlas.index != index as usize => {
+                    if let Some(atlas) = &mut sprite.texture_atlas {
+                        atlas.index = index as usize;
+                    }
+                }
+                (None, _, Some(image)) if sprite.image != *image => {
+                    sprite.image = image.clone();
+                }
+                _ => {}
+            }
+        }
+    }
+}
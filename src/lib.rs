@@ -3,9 +3,18 @@ pub mod button;
 pub mod player;
 pub mod screens;
 pub mod tiled;
+pub mod zone;
+
+/// Registers every type read from `Tiled` custom properties, without any of the game's systems.
+///
+/// This is all `--export-tiled-project` needs, so it can run without a window or GPU.
+pub fn register_tiled_property_types(app: &mut bevy::app::App) {
+    tiled::register_property_types(app);
+    zone::register_property_types(app);
+}
 
 pub mod helper {
-    use bevy::{ecs::system::SystemId, prelude::*};
+    use bevy::prelude::*;
 
     /// Component for a name.
     #[derive(Component, PartialEq, Eq, Default, Debug)]
@@ -14,8 +23,10 @@ pub mod helper {
     #[derive(Resource)]
     pub struct CurrentMap(pub Name);
 
-    #[allow(unused)]
-    #[derive(Debug, Default)]
+    /// The world-space area covered by a map.
+    ///
+    /// Every `Tiled` map entity gets one once its layers are spawned.
+    #[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
     pub struct Bounds {
         pub left: f32,
         pub right: f32,
@@ -23,67 +34,60 @@ pub mod helper {
         pub bottom: f32,
     }
 
+    impl Bounds {
+        /// Creates the bounds of a rectangle with the given center and half size.
+        pub fn from_center_half_size(center: Vec2, half_size: Vec2) -> Self {
+            Self {
+                left: center.x - half_size.x,
+                right: center.x + half_size.x,
+                top: center.y + half_size.y,
+                bottom: center.y - half_size.y,
+            }
+        }
+
+        /// The smallest bounds containing both `self` and `other`.
+        pub fn union(self, other: Self) -> Self {
+            Self {
+                left: self.left.min(other.left),
+                right: self.right.max(other.right),
+                top: self.top.max(other.top),
+                bottom: self.bottom.min(other.bottom),
+            }
+        }
+
+        /// Scales the bounds by `ratio` around `origin`.
+        pub fn scale_around(self, origin: Vec2, ratio: f32) -> Self {
+            Self {
+                left: origin.x + (self.left - origin.x) * ratio,
+                right: origin.x + (self.right - origin.x) * ratio,
+                top: origin.y + (self.top - origin.y) * ratio,
+                bottom: origin.y + (self.bottom - origin.y) * ratio,
+            }
+        }
+    }
+
+    /// The bounds of the current map, which the player and camera are kept within.
     #[derive(Debug, Default, Resource)]
     pub struct MapBounds(pub Bounds);
 
-    #[derive(Debug, Resource)]
-    pub struct CalculateBoundsId(pub SystemId);
+    /// The scale the maps and the player are drawn at.
+    ///
+    /// Changing it rescales the current map and the player in place.
+    #[derive(Debug, Clone, Copy, PartialEq, Resource)]
+    pub struct WorldScale(pub f32);
 
-    /// Claculates the bounds of the current map.
-    pub(crate) fn calculate_bounds(
-        current_map: Res<CurrentMap>,
-        maps: Res<Assets<crate::tiled::TiledMap>>,
-        window: Single<&Window>,
-        tilemaps: Query<(&crate::helper::Name, &crate::tiled::TiledMapHandle)>,
-        mut map_bounds: ResMut<MapBounds>,
-    ) {
-        let mut bounds = Bounds::default();
-        for (name, tilemap) in tilemaps {
-            if *name == current_map.0 {
-                info_once!("Tiled Map Handle: {:?}", &tilemap.0);
-                let tiled_map = maps.get(&tilemap.0);
-                info_once!("Tiled Map: {:?}", tiled_map);
-                if let Some(tiled_map) = tiled_map {
-                    let (map_width, map_height) =
-                        (tiled_map.map.width as f32, tiled_map.map.height as f32);
-                    let (tile_width, tile_height) = (
-                        tiled_map.map.tile_width as f32,
-                        tiled_map.map.tile_height as f32,
-                    );
-                    let right_bound = map_width * tile_width;
-                    let top_bound = map_height * tile_height;
-                    let left_bound = -right_bound;
-                    let bottom_bound = -top_bound;
-                    bounds = Bounds {
-                        left: left_bound,
-                        right: right_bound,
-                        top: top_bound,
-                        bottom: bottom_bound,
-                    };
-                    break;
-                } else {
-                    let window_size = window.size();
-                    bounds = Bounds {
-                        left: -window_size.x,
-                        right: window_size.x,
-                        top: window_size.y,
-                        bottom: -window_size.y,
-                    };
-                    break;
-                }
-            } else {
-                let window_size = window.size();
-                bounds = Bounds {
-                    left: -window_size.x,
-                    right: window_size.x,
-                    top: window_size.y,
-                    bottom: -window_size.y,
-                };
-                break;
-            }
+    impl Default for WorldScale {
+        fn default() -> Self {
+            Self(2.0)
+        }
+    }
+
+    impl WorldScale {
+        /// The scale as a `Transform` scale, which leaves the z-axis alone so the order of layers
+        /// doesn't change.
+        pub fn to_vec3(self) -> Vec3 {
+            Vec3::new(self.0, self.0, 1.0)
         }
-        info_once!("Map bounds calculated: {:?}", bounds);
-        map_bounds.0 = bounds;
     }
 }
 
@@ -92,23 +96,6 @@ pub mod helper {
 // TODO: Add loader to load all assets in the beginning!
 //  - Handle when asset isn't fully loaded
 //
-// TODO: Add animation using bevy_ecs_tilemap
-//
-// TODO: Add all `scale` constants as resources
-//
 // TODO: Replace external assests with custom ones!
 //
 // TODO: Make camera follow player
-//
-// TODO: Add `Zone { name: string }` property to tiles -> Adds a Zone component
-//  - Can query for this component to change maps when a new zone is entered
-//  - Setup maps the same way as screens
-//
-// TODO: Add `Zone { name: string }` property to tiles -> Adds a Zone component
-//  - Can query for this component to change maps when a new zone is entered
-//  - Setup maps the same way as screens
-//
-//
-// TODO: Add `Animated { sprite_sheet, start_idx, end_idx, duration }` property to tiles -> flags
-// for animation
-//  - Add to the tiled plugin, so map tiles can be individually animated
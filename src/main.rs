@@ -1,8 +1,31 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
+use terra_firma::register_tiled_property_types;
 use terra_firma::screens::{self};
+use terra_firma::tiled::export::export_property_types;
+
+/// The `Tiled` project updated by `--export-tiled-project` when no path is given.
+const TILED_PROJECT: &str = "assets/maps/map_00/map_00.tiled-project";
 
 fn main() {
+    // `cargo run -- --export-tiled-project [path]` writes the types registered for `Tiled` to the
+    // project instead of running the game. Only the types are registered, so it runs headless.
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("--export-tiled-project") {
+        let path = args.next().unwrap_or_else(|| TILED_PROJECT.into());
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        register_tiled_property_types(&mut app);
+        match export_property_types(app.world(), &path) {
+            Ok(()) => println!("Updated the property types of '{path}'."),
+            Err(error) => {
+                eprintln!("Could not update the property types of '{path}': {error}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins.set(ImagePlugin::default_nearest()),
@@ -6,12 +6,13 @@ use bevy::{
 
 use crate::{
     animation::{self, AnimationConfig},
-    helper::{self, CurrentMap, MapBounds},
+    helper::{self, CurrentMap, MapBounds, WorldScale},
     screens::Screen,
+    tiled::SpawnPoints,
 };
 
 /// Determines the layer the player is drawn on.
-const PLAYER_Z_IDX: f32 = 100.0;
+pub(crate) const PLAYER_Z_IDX: f32 = 100.0;
 
 /// Player movement speed factor.
 const PLAYER_SPEED: f32 = 200.0;
@@ -19,19 +20,27 @@ const PLAYER_SPEED: f32 = 200.0;
 /// How quickly should the camera snap to the desired location.
 const CAMERA_DECAY_RATE: f32 = 2.;
 
-/// Player sprite scale factor.
-const PLAYER_SCALE: f32 = 2.0;
-
 /// Marker component for the player.
 #[derive(Component)]
 pub(crate) struct Player;
 
+/// The name of the spawn point to place the player at once the current map has loaded.
+#[derive(Resource, Default, Debug)]
+pub(crate) struct PlayerSpawnPoint(pub(crate) Option<String>);
+
 /// Add the player systems to the app.
 pub(crate) fn add_systems(app: &mut App) {
+    app.init_resource::<PlayerSpawnPoint>();
     app.add_systems(OnEnter(Screen::Gameplay), setup);
     app.add_systems(
         Update,
-        (move_player, move_camera).run_if(in_state(Screen::Gameplay)),
+        (place_at_spawn_point, move_player, move_camera)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        scale_player.run_if(in_state(Screen::Gameplay).and(resource_changed::<WorldScale>)),
     );
     add_animation_systems(app);
 }
@@ -41,6 +50,8 @@ fn setup(
     mut cmd: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut spawn_point: ResMut<PlayerSpawnPoint>,
+    world_scale: Res<WorldScale>,
 ) {
     // Setup sprite
     let texture = asset_server.load("tileset/character-sprite-sheet.png");
@@ -66,8 +77,51 @@ fn setup(
         animation_config,
         RigidBody::Kinematic,
         Collider::rectangle(sprite_size.x as f32, sprite_size.y as f32),
-        Transform::from_xyz(0., 0., PLAYER_Z_IDX).with_scale(Vec3::splat(PLAYER_SCALE)),
+        Transform::from_xyz(0., 0., PLAYER_Z_IDX).with_scale(world_scale.to_vec3()),
     ));
+
+    // The map hasn't loaded yet, so the player is placed once its spawn points are known.
+    spawn_point.0 = Some("default".into());
+}
+
+/// Places the player at the requested spawn point once the current map's spawn points have been
+/// collected.
+fn place_at_spawn_point(
+    mut spawn_point: ResMut<PlayerSpawnPoint>,
+    current_map: Res<CurrentMap>,
+    spawn_points: Res<SpawnPoints>,
+    mut player_position: Single<&mut Transform, With<Player>>,
+) {
+    let Some(name) = &spawn_point.0 else {
+        return;
+    };
+    if spawn_points.map != current_map.0 .0 {
+        return;
+    }
+
+    match spawn_points.points.get(name) {
+        Some(position) => {
+            player_position.translation.x = position.x;
+            player_position.translation.y = position.y;
+        }
+        None => warn!(
+            "No spawn point named '{name}' in map '{}'.",
+            spawn_points.map
+        ),
+    }
+
+    spawn_point.0 = None;
+}
+
+/// Rescales the player when the world scale changes, keeping it at the same spot on the map.
+fn scale_player(
+    world_scale: Res<WorldScale>,
+    mut player_transform: Single<&mut Transform, With<Player>>,
+) {
+    let ratio = world_scale.0 / player_transform.scale.x;
+    player_transform.translation.x *= ratio;
+    player_transform.translation.y *= ratio;
+    player_transform.scale = world_scale.to_vec3();
 }
 
 /// Updates the player's position.
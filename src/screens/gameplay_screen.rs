@@ -1,14 +1,28 @@
 use crate::{
-    helper::{self, CalculateBoundsId, CurrentMap, MapBounds},
-    player::{self, Player},
+    helper::{self, Bounds, CurrentMap, WorldScale},
+    player::{self, Player, PlayerSpawnPoint},
     screens::Screen,
+    tiled::{RemoveMap, SpawnPoints, TiledLoaderSettings, TiledMapHandle},
+    zone::{self, ZoneEntered},
 };
 // use avian2d::prelude::*;
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 use bevy_ecs_tilemap::prelude::*;
 
-/// Map scale factor.
-const MAP_SCALE: f32 = 2.0;
+/// The name and `.tmx` file of each map.
+///
+/// The example maps cover each orientation `Tiled` supports, and can be reached from the zones in
+/// the bottom-left corner of the main map.
+const MAPS: &[(&str, &str)] = &[
+    ("Main", "maps/map_00/main.tmx"),
+    ("Orthogonal", "maps/examples/orthogonal.tmx"),
+    ("Isometric", "maps/examples/isometric.tmx"),
+    ("Staggered", "maps/examples/staggered.tmx"),
+    ("Hexagonal", "maps/examples/hexagonal.tmx"),
+];
+
+/// The `Tiled` project every map's custom properties are checked against.
+const TILED_PROJECT: &str = "maps/map_00/map_00.tiled-project";
 
 /// Marker component for the background mesh.
 #[derive(Component)]
@@ -16,9 +30,8 @@ struct BackgroundMesh;
 
 /// Bundles the systems of the `Gameplay` screen.
 pub(crate) fn plugin(app: &mut App) {
-    let calculate_bounds_id = app.register_system(helper::calculate_bounds);
     app.insert_resource(CurrentMap(helper::Name("Main".into())));
-    app.insert_resource(CalculateBoundsId(calculate_bounds_id));
+    app.init_resource::<WorldScale>();
 
     app.add_systems(OnEnter(Screen::Gameplay), setup);
     app.add_systems(OnExit(Screen::Gameplay), despawn_player);
@@ -26,34 +39,141 @@ pub(crate) fn plugin(app: &mut App) {
         Update,
         enter_main_screen.run_if(input_just_pressed(KeyCode::Escape)),
     );
+    app.add_systems(Update, change_map.run_if(in_state(Screen::Gameplay)));
+    app.add_systems(
+        Update,
+        scale_maps.run_if(in_state(Screen::Gameplay).and(resource_changed::<WorldScale>)),
+    );
     player::add_systems(app);
+    zone::add_systems(app);
 
-    app.add_plugins((crate::tiled::TiledMapPlugin, TilemapPlugin));
+    app.add_plugins((
+        crate::tiled::TiledMapPlugin,
+        crate::tiled::TiledColliderDebugPlugin::default(),
+        TilemapPlugin,
+    ));
 }
 
 /// Setups up the camera and spawns the map.
-fn setup(mut cmd: Commands, asset_server: Res<AssetServer>) {
+fn setup(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    current_map: Res<CurrentMap>,
+    world_scale: Res<WorldScale>,
+) {
     let mut projection = OrthographicProjection::default_2d();
     projection.scaling_mode = bevy::render::camera::ScalingMode::WindowSize;
-    let map_handle = crate::tiled::TiledMapHandle(asset_server.load("maps/map_00/main.tmx"));
+    let name = &current_map.0 .0;
+    let Some(path) = map_path(name) else {
+        return;
+    };
+    spawn_map(&mut cmd, &asset_server, name, path, *world_scale);
+}
+
+/// Finds the `.tmx` file of the map with the given name.
+fn map_path(name: &str) -> Option<&'static str> {
+    let path = MAPS
+        .iter()
+        .find(|(map_name, _)| *map_name == name)
+        .map(|(_, path)| *path);
+    if path.is_none() {
+        error!("No map named '{name}' exists.");
+    }
+    path
+}
+
+/// Spawns the map with the given name from its `.tmx` file.
+fn spawn_map(
+    cmd: &mut Commands,
+    asset_server: &AssetServer,
+    name: &str,
+    path: &'static str,
+    world_scale: WorldScale,
+) {
+    let map = asset_server.load_with_settings(path, |settings: &mut TiledLoaderSettings| {
+        settings.project = Some(TILED_PROJECT.into());
+    });
+    let map_handle = crate::tiled::TiledMapHandle(map);
     cmd.spawn((
         StateScoped(Screen::Gameplay),
         crate::tiled::TiledMapBundle {
-            name: helper::Name("Main".into()),
+            name: helper::Name(name.into()),
             tiled_map: map_handle,
-            transform: Transform::default().with_scale(Vec3::splat(MAP_SCALE)),
+            transform: Transform::default().with_scale(world_scale.to_vec3()),
             ..default()
         },
     ));
 }
 
+/// Replaces the current map with the one the player moved to.
+fn change_map(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    mut zone_entered: EventReader<ZoneEntered>,
+    mut current_map: ResMut<CurrentMap>,
+    mut player_spawn_point: ResMut<PlayerSpawnPoint>,
+    world_scale: Res<WorldScale>,
+    maps: Query<(Entity, &helper::Name), With<TiledMapHandle>>,
+) {
+    // Only the last zone matters, since the player can only end up in one map.
+    let Some(event) = zone_entered.read().last() else {
+        return;
+    };
+
+    if current_map.0 .0 != event.to {
+        // A zone leading to a map that doesn't exist leaves the player where they are.
+        let Some(path) = map_path(&event.to) else {
+            return;
+        };
+
+        for (map_entity, name) in maps {
+            if name.0 == event.from {
+                cmd.entity(map_entity).insert(RemoveMap);
+            }
+        }
+
+        spawn_map(&mut cmd, &asset_server, &event.to, path, *world_scale);
+        current_map.0 = helper::Name(event.to.clone());
+    }
+
+    player_spawn_point.0 = Some(event.spawn_point.clone());
+}
+
+/// Rescales the maps when the world scale changes.
+///
+/// The layers of a map are scaled along with it, while its spawn points and bounds are updated to
+/// match.
+fn scale_maps(
+    world_scale: Res<WorldScale>,
+    mut maps: Query<(&helper::Name, &mut Transform, Option<&mut Bounds>), With<TiledMapHandle>>,
+    mut spawn_points: ResMut<SpawnPoints>,
+) {
+    for (name, mut map_transform, bounds) in &mut maps {
+        let ratio = world_scale.0 / map_transform.scale.x;
+        let origin = map_transform.translation.truncate();
+        map_transform.scale = world_scale.to_vec3();
+
+        if spawn_points.map == name.0 {
+            for position in spawn_points.points.values_mut() {
+                *position = origin + (*position - origin) * ratio;
+            }
+        }
+
+        // Bounds are only known once the map has loaded.
+        if let Some(mut bounds) = bounds {
+            *bounds = bounds.scale_around(origin, ratio);
+        }
+    }
+}
+
 /// Switches to the main screen.
 fn enter_main_screen(mut next_state: ResMut<NextState<Screen>>) {
     next_state.set(Screen::Main);
 }
 
 /// Removes the player from the game.
+///
+/// Maps are scoped to the `Gameplay` screen, so they're despawned along with everything in them.
 fn despawn_player(mut cmd: Commands, player: Single<Entity, With<Player>>) {
-    // TODO: Despawn maps too!
     cmd.entity(*player).despawn();
 }
@@ -1,30 +1,76 @@
-use std::io::{Cursor, ErrorKind};
-use std::path::Path;
+use std::cell::Cell;
+use std::io::{Cursor, ErrorKind, Read};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use avian2d::parry::math::{Point, Real};
 use avian2d::prelude::*;
+use bevy::ecs::change_detection::{DetectChanges, Ref};
+use bevy::ecs::system::EntityCommands;
+use bevy::input::common_conditions::input_just_pressed;
 use bevy::log::{info, warn};
-use bevy::math::Vec3;
+use bevy::math::{Affine2, BVec2, IVec2, Mat2, Quat, URect, UVec2, Vec2, Vec3};
 use bevy::state::state::OnEnter;
 use bevy::{
-    asset::{io::Reader, AssetLoader, AssetPath},
-    platform::collections::HashMap,
+    asset::{
+        io::{AssetReaderError, AssetSourceId, Reader},
+        AssetLoadError, AssetLoader, AssetPath, LoadContext, LoadDirectError, ReadAssetBytesError,
+    },
+    color::{Alpha, Color, Srgba},
+    image::{ImageLoaderSettings, ImageSampler},
+    platform::collections::{HashMap, HashSet},
     prelude::{
-        Added, Asset, AssetApp, AssetEvent, AssetId, Assets, Bundle, Commands, Component, Entity,
-        EventReader, GlobalTransform, Handle, Image, Plugin, Query, Res, Transform, Update,
+        Added, AppGizmoBuilder, Asset, AssetApp, AssetEvent, AssetId, Assets, Bundle, Camera2d,
+        ChildOf, Children, Commands, Component, Entity, EventReader, GizmoConfig, GizmoConfigStore,
+        GlobalTransform, Handle, Image, IntoScheduleConfigs, KeyCode, Plugin, PostUpdate, Query,
+        Res, ResMut, Resource, Single, Sprite, SpriteImageMode, TextureAtlas, TextureAtlasLayout,
+        Transform, Update, Visibility, With, Without,
     },
     reflect::TypePath,
+    sprite::Anchor,
+    transform::TransformSystem,
 };
 use bevy_ecs_tilemap::prelude::*;
-
-use crate::helper::{self, CurrentMap, MapBounds, Name};
-
-const MAP_SCALE: f32 = 2.0;
+use serde::{Deserialize, Serialize};
+use xml::common::Position;
+
+use crate::animation::{self, TileAnimation, TileFrame};
+use crate::helper::{self, Bounds, CurrentMap, MapBounds, Name};
+use crate::player::PLAYER_Z_IDX;
+use crate::zone::Zone;
+
+use classes::{TiledClassAppExt, TiledClassRegistry};
+use error::TiledLoaderError;
+use grid::TiledGrid;
+use project::{InvalidProperties, TiledProject, TiledProjectLoader};
+use properties::{read_property, FromTiledProperties, Hitbox, PropertyError, TiledRigidBody};
+
+pub mod classes;
+pub mod error;
+pub mod export;
+pub mod grid;
+pub mod project;
+pub mod properties;
 
 /// A marker component for objects that can be collided with.
 #[derive(Component, Default, Debug)]
 pub struct TiledColliderObject;
 
+/// An entity created from a `Tiled` object.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TiledObject {
+    /// The id of the object in its map.
+    pub id: u32,
+}
+
+/// Registers the types of the custom properties read by the map loader.
+pub fn register_property_types(app: &mut bevy::prelude::App) {
+    app.init_resource::<TiledClassRegistry>();
+    app.register_tiled_property_type::<TiledRigidBody>("RigidBody")
+        .register_tiled_property_type::<Hitbox>("Hitbox");
+}
+
 /// Handles all systems for creating a map from `Tiled`.
 #[derive(Default)]
 pub struct TiledMapPlugin;
@@ -32,24 +78,943 @@ pub struct TiledMapPlugin;
 impl Plugin for TiledMapPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.insert_resource(MapBounds::default());
+        app.init_resource::<SpawnPoints>();
+        register_property_types(app);
+        app.init_asset::<TiledProject>()
+            .register_asset_loader(TiledProjectLoader);
         app.init_asset::<TiledMap>()
             .register_asset_loader(TiledLoader)
-            .add_systems(Update, process_loaded_maps);
+            .add_systems(
+                Update,
+                (
+                    despawn_tiled_maps.before(process_loaded_maps),
+                    process_loaded_maps,
+                    update_map_bounds.after(process_loaded_maps),
+                    update_spawn_points.after(process_loaded_maps),
+                    animation::execute_tile_animations,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_parallax.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Draws the colliders generated from maps (and every other collider) while toggled on with the
+/// `toggle_key`.
+pub struct TiledColliderDebugPlugin {
+    pub toggle_key: KeyCode,
+}
+
+impl Default for TiledColliderDebugPlugin {
+    fn default() -> Self {
+        Self {
+            toggle_key: KeyCode::F3,
+        }
     }
 }
 
+impl Plugin for TiledColliderDebugPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(
+            Update,
+            toggle_collider_debug.run_if(input_just_pressed(self.toggle_key)),
+        );
+
+        // Colliders are only drawn once toggled on, unless the app already draws them.
+        if !app.is_plugin_added::<PhysicsDebugPlugin>() {
+            app.add_plugins(PhysicsDebugPlugin::default());
+            app.insert_gizmo_config(
+                PhysicsGizmos::default(),
+                GizmoConfig {
+                    enabled: false,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+/// Toggles drawing the colliders generated from maps (and every other collider).
+fn toggle_collider_debug(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<PhysicsGizmos>();
+    config.enabled = !config.enabled;
+    info!(
+        "Collider debug view {}",
+        if config.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+}
+
 /// The map loaded by the `TiledLoader`.
 #[derive(TypePath, Asset, Debug)]
 pub struct TiledMap {
     pub map: tiled::Map,
 
     pub tilemap_textures: HashMap<usize, TilemapTexture>,
+
+    /// The size of the tiles in each tileset's tilemap texture.
+    pub tile_sizes: HashMap<usize, TilemapTileSize>,
+
+    /// The index of each image collection tile within its tileset's `TilemapTexture::Vector`.
+    pub tile_image_offsets: HashMap<(usize, tiled::TileId), u32>,
+
+    /// Image collection tiles that don't match their tileset's tile size, which are drawn as
+    /// sprites instead of tilemap tiles.
+    pub tile_sprites: HashMap<(usize, tiled::TileId), TileSprite>,
+
+    /// The texture atlas layout of each tileset with a single image, used to draw tile objects.
+    pub tile_atlas_layouts: HashMap<usize, Handle<TextureAtlasLayout>>,
+
+    /// The image of each image layer, by the id of the layer.
+    pub image_layers: HashMap<u32, Handle<Image>>,
+
+    /// The axes each image layer repeats its image along, by the id of the layer.
+    pub image_layer_repeats: HashMap<u32, BVec2>,
+
+    /// The size of the map in tiles.
+    ///
+    /// For infinite maps this covers the extents of all occupied chunks.
+    pub map_size: TilemapSize,
+
+    /// The position (in `Tiled` tile coordinates) of the top-left tile of the map.
+    ///
+    /// This is always `(0, 0)` for finite maps.
+    pub tile_origin: IVec2,
+
+    /// The settings the map was loaded with.
+    pub settings: TiledLoaderSettings,
 }
 
-// Stores a list of tiled layers.
-#[derive(Component, Default)]
+/// The spawn points of the current map, in world coordinates.
+///
+/// Created from objects of the `SpawnPoint` class in `Tiled`.
+#[derive(Resource, Default, Debug)]
+pub struct SpawnPoints {
+    /// The name of the map the spawn points belong to.
+    pub map: String,
+
+    pub points: HashMap<String, Vec2>,
+}
+
+/// The spawn points of a map entity, in world coordinates.
+#[derive(Component, Default, Debug)]
+struct MapSpawnPoints(HashMap<String, Vec2>);
+
+impl TiledMap {
+    /// Converts a position in `Tiled` pixel coordinates on a layer with the given offset into the
+    /// local coordinates of the map, whose origin is the center of the map.
+    ///
+    /// The offset should include the offsets of the groups the layer is in. The position is
+    /// scaled by the `scale` the map was loaded with.
+    pub fn to_map_position(&self, layer_offset: Vec2, x: f32, y: f32) -> Vec2 {
+        // Layer offsets are applied after positions are projected onto the grid.
+        let offset = Vec2::new(layer_offset.x, -layer_offset.y);
+        (self.grid().pixel_to_local(x, y) + offset) * self.settings.scale
+    }
+
+    /// The layout of the map's tiles.
+    pub fn grid(&self) -> TiledGrid {
+        TiledGrid::new(&self.map, self.map_size, self.tile_origin)
+    }
+}
+
+/// Reads a custom property of an object, logging a warning and ignoring it if it's invalid.
+fn object_property<T: FromTiledProperties>(
+    map_path: &str,
+    object_data: &tiled::ObjectData,
+    name: &str,
+) -> Option<T> {
+    read_property(&object_data.properties, name).unwrap_or_else(|error: PropertyError| {
+        warn!("{}", error.on_object(object_data.id()).in_map(map_path));
+        None
+    })
+}
+
+/// Reads a custom property of a layer, logging a warning and ignoring it if it's invalid.
+fn layer_property<T: FromTiledProperties>(
+    map_path: &str,
+    layer: &tiled::Layer,
+    name: &str,
+) -> Option<T> {
+    read_property(&layer.properties, name).unwrap_or_else(|error: PropertyError| {
+        warn!("{}", error.on_layer(&layer.name).in_map(map_path));
+        None
+    })
+}
+
+/// Inserts the components registered for the class of an object, logging a warning and skipping
+/// them if its properties are invalid.
+///
+/// Tile objects use the class of their tile unless they have their own, and inherit the properties
+/// of their tile.
+fn insert_object_classes(
+    registry: &TiledClassRegistry,
+    entity: &mut EntityCommands,
+    map_path: &str,
+    object_data: &tiled::ObjectData,
+    tile: Option<&tiled::TileData>,
+) {
+    let mut class = object_data.user_type.as_str();
+    let mut properties = tiled::Properties::new();
+    if let Some(tile) = tile {
+        if class.is_empty() {
+            class = tile.user_type.as_deref().unwrap_or_default();
+        }
+        properties.extend(tile.properties.clone());
+    }
+    properties.extend(object_data.properties.clone());
+
+    if let Err(error) = registry.insert(entity, class, &properties) {
+        warn!("{}", error.on_object(object_data.id()).in_map(map_path));
+    }
+}
+
+/// Inserts the components registered for the class of a tile placed on a tile layer, logging a
+/// warning and skipping them if its properties are invalid.
+fn insert_tile_classes(
+    registry: &TiledClassRegistry,
+    entity: &mut EntityCommands,
+    map_path: &str,
+    tileset: &tiled::Tileset,
+    tile_id: tiled::TileId,
+) {
+    let Some(tile) = tileset.get_tile(tile_id) else {
+        return;
+    };
+    let Some(class) = &tile.user_type else {
+        return;
+    };
+
+    if let Err(error) = registry.insert(entity, class, &tile.properties) {
+        warn!(
+            "{} (tile {tile_id} in tileset '{}')",
+            error.in_map(map_path),
+            tileset.name
+        );
+    }
+}
+
+/// Creates a collider matching the shape of a `Tiled` object.
+///
+/// The collider is positioned relative to the object's origin, which is the top-left corner of
+/// rectangles and ellipses. Points act as a zero-radius circle, so they can still be overlapped.
+/// Shapes that can't be made into a collider, such as polygons without any area, return `None`.
+///
+/// Objects placed on an isometric map's `grid` have their shapes projected onto it, which turns
+/// rectangles into diamonds.
+fn shape_collider(shape: &tiled::ObjectShape, grid: Option<&TiledGrid>) -> Option<Collider> {
+    let projection = grid.filter(|grid| grid.is_isometric());
+
+    // Tiled's y-axis points down, while Bevy's points up.
+    let to_local = |x: f32, y: f32| match projection {
+        Some(grid) => grid.object_offset(Vec2::new(x, y)),
+        None => Vec2::new(x, -y),
+    };
+    let to_vertices = |points: &[(f32, f32)]| {
+        points
+            .iter()
+            .map(|(x, y)| to_local(*x, *y))
+            .collect::<Vec<_>>()
+    };
+
+    let collider = match shape {
+        tiled::ObjectShape::Rect { width, height } if projection.is_some() => {
+            Collider::convex_hull(vec![
+                to_local(0.0, 0.0),
+                to_local(*width, 0.0),
+                to_local(*width, *height),
+                to_local(0.0, *height),
+            ])?
+        }
+
+        tiled::ObjectShape::Ellipse { width, height } if projection.is_some() => {
+            const SEGMENTS: usize = 16;
+            let points = (0..SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                    to_local(
+                        width / 2.0 * (1.0 + angle.cos()),
+                        height / 2.0 * (1.0 + angle.sin()),
+                    )
+                })
+                .collect();
+            Collider::convex_hull(points)?
+        }
+
+        tiled::ObjectShape::Rect { width, height } => Collider::compound(vec![(
+            Vec2::new(width / 2.0, -height / 2.0),
+            Rotation::default(),
+            Collider::rectangle(*width, *height),
+        )]),
+
+        tiled::ObjectShape::Ellipse { width, height } => Collider::compound(vec![(
+            Vec2::new(width / 2.0, -height / 2.0),
+            Rotation::default(),
+            Collider::ellipse(width / 2.0, height / 2.0),
+        )]),
+
+        tiled::ObjectShape::Polygon { points } if points.len() >= 3 => {
+            let vertices = to_vertices(points);
+
+            // Polygons without any area (such as ones with all their points on a line) have no
+            // convex parts, which can't be made into a collider.
+            Collider::convex_hull(vertices.clone())?;
+
+            // Concave polygons can't be used directly, so they're split into convex parts.
+            let indices = (0..points.len() as u32)
+                .map(|i| [i, (i + 1) % points.len() as u32])
+                .collect();
+            Collider::convex_decomposition(vertices, indices)
+        }
+
+        tiled::ObjectShape::Polyline { points } if points.len() >= 2 => {
+            Collider::polyline(to_vertices(points), None)
+        }
+
+        tiled::ObjectShape::Point(_, _) => Collider::circle(0.0),
+
+        _ => return None,
+    };
+
+    Some(collider)
+}
+
+/// Adds a collider to the parts of a compound collider.
+///
+/// Compound colliders can't be nested, so the parts of any compound collider are added instead.
+fn push_compound_part(
+    parts: &mut Vec<(Vec2, Rotation, Collider)>,
+    position: Vec2,
+    rotation: Rotation,
+    collider: Collider,
+) {
+    let Some(compound) = collider.shape().as_compound() else {
+        parts.push((position, rotation, collider));
+        return;
+    };
+
+    for (isometry, shape) in compound.shapes() {
+        let part_position = Vec2::new(isometry.translation.x, isometry.translation.y);
+        parts.push((
+            position + rotation * part_position,
+            rotation * Rotation::radians(isometry.rotation.angle()),
+            Collider::from(shape.clone()),
+        ));
+    }
+}
+
+/// Creates colliders from the shapes drawn for a tile in the tileset's collision editor.
+///
+/// The colliders are positioned relative to the top-left corner of the tile.
+fn collision_shapes(
+    tileset: &tiled::Tileset,
+    tile_id: tiled::TileId,
+) -> Vec<(Vec2, Rotation, Collider)> {
+    let mut shapes = Vec::new();
+    let Some(tile) = tileset.get_tile(tile_id) else {
+        return shapes;
+    };
+    let Some(collision) = &tile.collision else {
+        return shapes;
+    };
+
+    for object_data in collision.object_data() {
+        let Some(collider) = shape_collider(&object_data.shape, None) else {
+            warn!(
+                "Skipping unsupported collision shape {} of tile {tile_id} in tileset '{}'.",
+                object_data.id(),
+                tileset.name
+            );
+            continue;
+        };
+
+        // Tiled rotates clockwise around the object's origin.
+        push_compound_part(
+            &mut shapes,
+            Vec2::new(object_data.x, -object_data.y),
+            Rotation::radians(-object_data.rotation.to_radians()),
+            collider,
+        );
+    }
+    shapes
+}
+
+/// Whether each tile of a layer is solid, or the collision shapes it adds to the layer otherwise, by
+/// the tile's id and whether it's flipped horizontally, vertically and diagonally.
+type TileCollisions =
+    HashMap<(tiled::TileId, bool, bool, bool), (bool, Vec<(Vec2, Rotation, Collider)>)>;
+
+/// Flips the collision shapes of a tile the same way its image is flipped.
+///
+/// The shapes are relative to the top-left corner of the tile's image, which has the given size.
+/// Like in `Tiled`, the image is flipped diagonally (swapping its axes) before it's flipped
+/// horizontally and vertically.
+fn flip_collision_shapes(
+    shapes: Vec<(Vec2, Rotation, Collider)>,
+    size: Vec2,
+    flip: TileFlip,
+) -> Vec<(Vec2, Rotation, Collider)> {
+    if !(flip.x || flip.y || flip.d) {
+        return shapes;
+    }
+
+    // The y-axis points up, so the image covers `0..width` and `-height..0`.
+    let mut transform = Affine2::IDENTITY;
+    let mut size = size;
+    if flip.d {
+        transform = Affine2::from_cols(Vec2::NEG_Y, Vec2::NEG_X, Vec2::ZERO) * transform;
+        size = Vec2::new(size.y, size.x);
+    }
+    if flip.x {
+        transform = Affine2::from_cols(Vec2::NEG_X, Vec2::Y, Vec2::new(size.x, 0.0)) * transform;
+    }
+    if flip.y {
+        transform = Affine2::from_cols(Vec2::X, Vec2::NEG_Y, Vec2::new(0.0, -size.y)) * transform;
+    }
+
+    // Mirrored shapes are mirrored along their own x-axis, and then rotated into place.
+    let is_mirrored = transform.matrix2.determinant() < 0.0;
+    let rotation_matrix = if is_mirrored {
+        transform.matrix2 * Mat2::from_diagonal(Vec2::new(-1.0, 1.0))
+    } else {
+        transform.matrix2
+    };
+    let rotation = Rotation::radians(rotation_matrix.x_axis.to_angle());
+
+    shapes
+        .into_iter()
+        .map(|(position, part_rotation, collider)| {
+            let position = transform.transform_point2(position);
+            if is_mirrored {
+                (
+                    position,
+                    rotation * part_rotation.inverse(),
+                    mirror_collider(&collider),
+                )
+            } else {
+                (position, rotation * part_rotation, collider)
+            }
+        })
+        .collect()
+}
+
+/// Mirrors a collider along its local x-axis.
+///
+/// Shapes that are symmetric along the y-axis (such as rectangles and circles) are left as they
+/// are.
+fn mirror_collider(collider: &Collider) -> Collider {
+    let mirror = |point: &Point<Real>| Vec2::new(-point.x, point.y);
+    let shape = collider.shape();
+    if let Some(polygon) = shape.as_convex_polygon() {
+        let points = polygon.points().iter().map(mirror).collect();
+        Collider::convex_hull(points).unwrap_or_else(|| collider.clone())
+    } else if let Some(polyline) = shape.as_polyline() {
+        let vertices = polyline.vertices().iter().map(mirror).collect();
+        Collider::polyline(vertices, Some(polyline.indices().to_vec()))
+    } else if let Some(triangle) = shape.as_triangle() {
+        Collider::triangle(
+            mirror(&triangle.a),
+            mirror(&triangle.b),
+            mirror(&triangle.c),
+        )
+    } else if let Some(segment) = shape.as_segment() {
+        Collider::segment(mirror(&segment.a), mirror(&segment.b))
+    } else {
+        collider.clone()
+    }
+}
+
+/// Checks whether a tile's collision is a single rectangle covering the whole tile, so it can be
+/// merged with neighbouring solid tiles.
+///
+/// Merged tiles cover whole cells of the map's grid, so tiles of any other size are never solid.
+fn is_solid_tile(
+    tileset: &tiled::Tileset,
+    tile_id: tiled::TileId,
+    grid_size: &TilemapGridSize,
+) -> bool {
+    const TOLERANCE: f32 = 0.01;
+    let tile_width = tileset.tile_width as f32;
+    let tile_height = tileset.tile_height as f32;
+    if (tile_width - grid_size.x).abs() >= TOLERANCE
+        || (tile_height - grid_size.y).abs() >= TOLERANCE
+    {
+        return false;
+    }
+
+    let Some(tile) = tileset.get_tile(tile_id) else {
+        return false;
+    };
+    let Some(collision) = &tile.collision else {
+        return false;
+    };
+    let [object_data] = collision.object_data() else {
+        return false;
+    };
+    let tiled::ObjectShape::Rect { width, height } = object_data.shape else {
+        return false;
+    };
+
+    object_data.x.abs() < TOLERANCE
+        && object_data.y.abs() < TOLERANCE
+        && object_data.rotation.abs() < TOLERANCE
+        && (width - tile_width).abs() < TOLERANCE
+        && (height - tile_height).abs() < TOLERANCE
+}
+
+/// Greedily merges the solid tiles of a layer into as few rectangles as possible.
+///
+/// `solid_tiles` is indexed by `y * map_size.x + x`, and the returned rectangles are in tile
+/// coordinates with inclusive bounds.
+fn merge_solid_tiles(solid_tiles: &[bool], map_size: TilemapSize) -> Vec<URect> {
+    let index = |x: u32, y: u32| (y * map_size.x + x) as usize;
+    let mut merged = vec![false; solid_tiles.len()];
+    let mut rects = Vec::new();
+
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            let is_free = |x: u32, y: u32| solid_tiles[index(x, y)] && !merged[index(x, y)];
+            if !is_free(x, y) {
+                continue;
+            }
+
+            // Grow to the right as far as possible, then grow upwards for as long as the whole
+            // row is solid.
+            let mut max_x = x;
+            while max_x + 1 < map_size.x && is_free(max_x + 1, y) {
+                max_x += 1;
+            }
+            let mut max_y = y;
+            while max_y + 1 < map_size.y && (x..=max_x).all(|x| is_free(x, max_y + 1)) {
+                max_y += 1;
+            }
+
+            for merged_y in y..=max_y {
+                for merged_x in x..=max_x {
+                    merged[index(merged_x, merged_y)] = true;
+                }
+            }
+            rects.push(URect::new(x, y, max_x, max_y));
+        }
+    }
+
+    rects
+}
+
+/// Creates a sprite showing the given tile, for tiles that aren't placed on the tile grid.
+fn object_tile_sprite(
+    tiled_map: &TiledMap,
+    tileset_index: usize,
+    tile_id: tiled::TileId,
+) -> Option<Sprite> {
+    match tiled_map.tilemap_textures.get(&tileset_index)? {
+        TilemapTexture::Single(image) => Some(Sprite::from_atlas_image(
+            image.clone(),
+            TextureAtlas {
+                layout: tiled_map.tile_atlas_layouts.get(&tileset_index)?.clone(),
+                index: tile_id as usize,
+            },
+        )),
+
+        TilemapTexture::Vector(images) => {
+            let image = match tiled_map.tile_image_offsets.get(&(tileset_index, tile_id)) {
+                Some(offset) => images[*offset as usize].clone(),
+                None => tiled_map
+                    .tile_sprites
+                    .get(&(tileset_index, tile_id))?
+                    .image
+                    .clone(),
+            };
+            Some(Sprite::from_image(image))
+        }
+
+        _ => None,
+    }
+}
+
+/// An image collection tile that is drawn as a standalone sprite.
+#[derive(Debug, Clone)]
+pub struct TileSprite {
+    pub image: Handle<Image>,
+
+    /// The size of the image in pixels.
+    pub size: Vec2,
+}
+
+/// Calculates the top-left tile and size (in tiles) of the given map.
+///
+/// Infinite maps don't have a fixed size, so the extents of all occupied chunks are used instead.
+fn tile_extents(map: &tiled::Map) -> (IVec2, TilemapSize) {
+    if !map.infinite() {
+        return (
+            IVec2::ZERO,
+            TilemapSize {
+                x: map.width,
+                y: map.height,
+            },
+        );
+    }
+
+    let mut min_chunk = IVec2::MAX;
+    let mut max_chunk = IVec2::MIN;
+    for layer in flatten_layers(map.layers()) {
+        if let tiled::LayerType::Tiles(tiled::TileLayer::Infinite(layer_data)) = layer.layer_type()
+        {
+            for ((chunk_x, chunk_y), _) in layer_data.chunks() {
+                min_chunk = min_chunk.min(IVec2::new(chunk_x, chunk_y));
+                max_chunk = max_chunk.max(IVec2::new(chunk_x, chunk_y));
+            }
+        }
+    }
+
+    // No chunks were found, so there is nothing to spawn.
+    if min_chunk.cmpgt(max_chunk).any() {
+        return (IVec2::ZERO, TilemapSize { x: 1, y: 1 });
+    }
+
+    let chunk_size = IVec2::new(
+        tiled::ChunkData::WIDTH as i32,
+        tiled::ChunkData::HEIGHT as i32,
+    );
+    let num_tiles = (max_chunk - min_chunk + IVec2::ONE) * chunk_size;
+    (
+        min_chunk * chunk_size,
+        TilemapSize {
+            x: num_tiles.x as u32,
+            y: num_tiles.y as u32,
+        },
+    )
+}
+
+/// Lists the layers of a map, replacing each group with the layers within it.
+fn flatten_layers<'map>(
+    layers: impl Iterator<Item = tiled::Layer<'map>>,
+) -> Vec<tiled::Layer<'map>> {
+    let mut flattened = Vec::new();
+    for layer in layers {
+        match layer.layer_type() {
+            tiled::LayerType::Group(group) => flattened.extend(flatten_layers(group.layers())),
+            _ => flattened.push(layer),
+        }
+    }
+    flattened
+}
+
+/// A layer of a map, along with what it inherits from the groups it's in.
+struct MapLayer<'map> {
+    layer: tiled::Layer<'map>,
+
+    /// The position of the layer in the order layers are drawn.
+    index: usize,
+
+    /// The entity of the group the layer is in, or the map itself.
+    parent: Entity,
+
+    /// The offset of the layer in `Tiled` pixels, including the offsets of its groups.
+    offset: Vec2,
+
+    /// The opacity of the layer, multiplied by the opacity of its groups.
+    opacity: f32,
+
+    /// The tint of the layer, multiplied by the tint of its groups.
+    tint: Srgba,
+
+    /// The parallax factor of the layer, multiplied by the parallax factor of its groups.
+    parallax: Vec2,
+
+    /// The depth the layer is drawn at.
+    z: f32,
+}
+
+impl MapLayer<'_> {
+    /// The color to draw the layer's tiles, tile objects and images with.
+    fn color(&self) -> Color {
+        Color::from(self.tint.with_alpha(self.tint.alpha * self.opacity))
+    }
+
+    /// The visibility of the layer's entity, which is also hidden by any hidden group it's in.
+    fn visibility(&self) -> Visibility {
+        if self.layer.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    }
+}
+
+/// Marks the entity spawned for a `Tiled` group layer.
+///
+/// Groups don't move the layers within them, since their offsets are already included in the
+/// offsets of those layers.
+#[derive(Component, Debug)]
+pub struct TiledGroupLayer {
+    /// The id of the layer in its map.
+    pub id: u32,
+}
+
+/// Moves a layer relative to the camera, so it scrolls at a different speed than the rest of its
+/// map.
+///
+/// Added to the tile and image layers with a parallax factor other than `1` in `Tiled`. Colliders
+/// aren't generated for the tiles of these layers, since they'd move along with them, while
+/// objects aren't moved at all.
+#[derive(Component, Debug)]
+pub struct TiledParallax {
+    /// How far the layer scrolls along with the map, where `0` keeps it fixed on screen.
+    pub factor: Vec2,
+
+    /// The map the layer belongs to.
+    pub map: Entity,
+
+    /// The offset currently added to the layer's translation.
+    offset: Vec2,
+}
+
+impl TiledParallax {
+    /// Creates the parallax of a layer, if it scrolls differently from its map.
+    fn new(factor: Vec2, map: Entity) -> Option<Self> {
+        (factor != Vec2::ONE).then_some(Self {
+            factor,
+            map,
+            offset: Vec2::ZERO,
+        })
+    }
+}
+
+/// Marks the entity spawned for a `Tiled` tile layer, which contains a tilemap for each tileset the
+/// layer uses.
+#[derive(Component, Debug)]
+pub struct TiledTileLayer {
+    /// The id of the layer in its map.
+    pub id: u32,
+}
+
+/// Marks the entity spawned for a `Tiled` object layer, which contains the layer's objects.
+#[derive(Component, Debug)]
+pub struct TiledObjectLayer {
+    /// The id of the layer in its map.
+    pub id: u32,
+}
+
+/// Marks the sprite spawned for a `Tiled` image layer.
+#[derive(Component, Debug)]
+pub struct TiledImageLayer {
+    /// The id of the layer in its map.
+    pub id: u32,
+}
+
+/// What the layers within a group inherit from it (and the groups it's in).
+#[derive(Clone, Copy)]
+struct LayerGroup {
+    /// The entity of the group, or the map itself.
+    entity: Entity,
+    offset: Vec2,
+    opacity: f32,
+    tint: Srgba,
+    parallax: Vec2,
+
+    /// Whether the layers within the group are drawn above the player, unless they say otherwise.
+    above_player: bool,
+}
+
+impl LayerGroup {
+    /// The map itself, which the top-level layers are in.
+    fn map(map_entity: Entity) -> Self {
+        Self {
+            entity: map_entity,
+            offset: Vec2::ZERO,
+            opacity: 1.0,
+            tint: Srgba::WHITE,
+            parallax: Vec2::ONE,
+            above_player: false,
+        }
+    }
+}
+
+/// Spawns an entity for each group layer in `layers`, and collects every other layer along with
+/// what it inherits from its groups.
+///
+/// Layers that aren't included by the map's settings are skipped, along with every layer within
+/// an excluded group.
+///
+/// Layers are drawn in order, one `layer_z_spacing` apart. Layers (or groups) with the
+/// `render_above_player` property are drawn above the player instead, and layers with a `z`
+/// property are drawn at exactly that depth.
+fn collect_layers<'map>(
+    commands: &mut Commands,
+    storage: &mut TiledLayersStorage,
+    settings: &TiledLoaderSettings,
+    map_path: &str,
+    layers: impl Iterator<Item = tiled::Layer<'map>>,
+    parent: LayerGroup,
+    collected: &mut Vec<MapLayer<'map>>,
+) {
+    for layer in layers {
+        let tint = layer.tint_color.map_or(Srgba::WHITE, |color| {
+            Srgba::rgba_u8(color.red, color.green, color.blue, color.alpha)
+        });
+
+        let index = collected.len();
+        let above_player =
+            layer_property(map_path, &layer, "render_above_player").unwrap_or(parent.above_player);
+        let z = layer_property(map_path, &layer, "z").unwrap_or_else(|| {
+            let z = index as f32 * settings.layer_z_spacing;
+            if above_player {
+                PLAYER_Z_IDX + 1.0 + z
+            } else {
+                z
+            }
+        });
+
+        let map_layer = MapLayer {
+            index,
+            parent: parent.entity,
+            offset: parent.offset + Vec2::new(layer.offset_x, layer.offset_y),
+            opacity: parent.opacity * layer.opacity,
+            tint: Srgba::new(
+                parent.tint.red * tint.red,
+                parent.tint.green * tint.green,
+                parent.tint.blue * tint.blue,
+                parent.tint.alpha * tint.alpha,
+            ),
+            parallax: parent.parallax * Vec2::new(layer.parallax_x, layer.parallax_y),
+            z,
+            layer,
+        };
+
+        if let tiled::LayerType::Group(group) = map_layer.layer.layer_type() {
+            if settings.exclude_layers.contains(&map_layer.layer.name) {
+                continue;
+            }
+
+            let group_entity = commands
+                .spawn((
+                    TiledGroupLayer {
+                        id: map_layer.layer.id(),
+                    },
+                    Transform::default(),
+                    map_layer.visibility(),
+                    ChildOf(parent.entity),
+                ))
+                .id();
+            storage.insert_name(&map_layer.layer.name, group_entity);
+            let group_parent = LayerGroup {
+                entity: group_entity,
+                offset: map_layer.offset,
+                opacity: map_layer.opacity,
+                tint: map_layer.tint,
+                parallax: map_layer.parallax,
+                above_player,
+            };
+            collect_layers(
+                commands,
+                storage,
+                settings,
+                map_path,
+                group.layers(),
+                group_parent,
+                collected,
+            );
+            continue;
+        }
+
+        if settings.includes_layer(&map_layer.layer.name) {
+            collected.push(map_layer);
+        }
+    }
+}
+
+/// Creates the animation for the given tile from its `Tiled` frame data, if it has one.
+fn tile_animation(
+    tiled_map: &TiledMap,
+    tileset_index: usize,
+    tile_id: tiled::TileId,
+) -> Option<TileAnimation> {
+    let tile = tiled_map.map.tilesets()[tileset_index].get_tile(tile_id)?;
+    let frames = tile.animation.as_ref()?;
+    let tilemap_texture = tiled_map.tilemap_textures.get(&tileset_index)?;
+
+    TileAnimation::new(frames.iter().filter_map(|frame| {
+        let tile_key = (tileset_index, frame.tile_id);
+        let (texture_index, image) = match tilemap_texture {
+            TilemapTexture::Single(_) => (Some(frame.tile_id), None),
+
+            // Image collection tiles are drawn from their own images, which differently-sized
+            // tiles only have as sprites.
+            TilemapTexture::Vector(images) => match tiled_map.tile_image_offsets.get(&tile_key) {
+                Some(offset) => (Some(*offset), images.get(*offset as usize).cloned()),
+                None => (
+                    None,
+                    Some(tiled_map.tile_sprites.get(&tile_key)?.image.clone()),
+                ),
+            },
+            _ => return None,
+        };
+        Some(TileFrame {
+            texture_index,
+            image,
+            duration: Duration::from_millis(frame.duration as u64),
+        })
+    }))
+}
+
+/// The entities spawned for the layers of a map.
+///
+/// Tile layers draw their tiles with a tilemap for each tileset they use, which are spawned within
+/// the layer's entity.
+#[derive(Component, Default, Debug)]
 pub struct TiledLayersStorage {
+    /// The entity of each tile, object and image layer, by the layer's position in the order
+    /// layers are drawn.
     pub storage: HashMap<u32, Entity>,
+
+    /// The tilemaps of the tile layers, by the layer's position in the order layers are drawn and
+    /// the index of the tileset they draw.
+    pub tilemaps: HashMap<(u32, usize), Entity>,
+
+    /// The entity of each layer (including groups) by name.
+    names: HashMap<String, Entity>,
+}
+
+impl TiledLayersStorage {
+    /// Gets the entity of the layer (or group) with the given name.
+    ///
+    /// `Tiled` doesn't require layer names to be unique, so this is the first layer with the name
+    /// in the map.
+    pub fn get_layer_by_name(&self, name: &str) -> Option<Entity> {
+        self.names.get(name).copied()
+    }
+
+    /// Iterates over the tilemaps of the tile layer at the given position in the order layers are
+    /// drawn, along with the index of the tileset each of them draws.
+    pub fn tilemaps_of(&self, layer_index: u32) -> impl Iterator<Item = (usize, Entity)> + '_ {
+        self.tilemaps
+            .iter()
+            .filter(move |((index, _), _)| *index == layer_index)
+            .map(|((_, tileset_index), entity)| (*tileset_index, *entity))
+    }
+
+    /// Records the entity of the layer at the given position in the order layers are drawn.
+    fn insert_layer(&mut self, index: usize, name: &str, entity: Entity) {
+        self.storage.insert(index as u32, entity);
+        self.insert_name(name, entity);
+    }
+
+    /// Records the entity of a layer with the given name, unless an earlier layer has the name.
+    fn insert_name(&mut self, name: &str, entity: Entity) {
+        self.names.entry(name.to_string()).or_insert(entity);
+    }
+
+    fn clear(&mut self) {
+        self.storage.clear();
+        self.tilemaps.clear();
+        self.names.clear();
+    }
 }
 
 /// Handle for the tiled map.
@@ -64,95 +1029,515 @@ pub struct TiledMapBundle {
     pub storage: TiledLayersStorage,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
     pub render_settings: TilemapRenderSettings,
 }
 
-struct BytesResourceReader {
-    bytes: Arc<[u8]>,
+/// Marks a map to be despawned, along with all of its layers, tiles and objects.
+///
+/// Despawning the map entity directly also removes everything spawned for it, but leaves its spawn
+/// points behind.
+#[derive(Component, Debug, Default)]
+pub struct RemoveMap;
+
+/// Despawns the maps marked with `RemoveMap`.
+fn despawn_tiled_maps(
+    mut commands: Commands,
+    maps: Query<(Entity, &Name), With<RemoveMap>>,
+    mut spawn_points: ResMut<SpawnPoints>,
+) {
+    for (map_entity, name) in &maps {
+        if spawn_points.map == name.0 {
+            *spawn_points = SpawnPoints::default();
+        }
+        commands.entity(map_entity).despawn();
+    }
 }
 
-impl BytesResourceReader {
-    fn new(bytes: &[u8]) -> Self {
-        Self {
-            bytes: Arc::from(bytes),
+/// Resolves a path from a map into an asset path in the same asset source as the map.
+///
+/// `tiled` joins relative paths onto the directory of the file containing them, so their `.` and
+/// `..` segments are resolved here. Paths starting with an asset source (like
+/// `embedded://tiles.png`) refer to that source instead.
+fn resolve_asset_path(
+    map_path: &AssetPath,
+    path: &Path,
+) -> Result<AssetPath<'static>, TiledLoaderError> {
+    let invalid_path = |reason: &str| TiledLoaderError::InvalidPath {
+        path: path.to_path_buf(),
+        reason: reason.into(),
+    };
+    let mut source = map_path.source().clone_owned();
+    let mut resolved = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if !resolved.pop() {
+                    return Err(invalid_path("it's outside of the asset source"));
+                }
+            }
+            std::path::Component::Normal(segment) => {
+                // Joining `source://file` onto a directory leaves the source as a segment ending
+                // in `:`.
+                match segment
+                    .to_str()
+                    .and_then(|segment| segment.strip_suffix(':'))
+                {
+                    Some(source_name) => {
+                        source = AssetSourceId::from(source_name).clone_owned();
+                        resolved.clear();
+                    }
+                    None => resolved.push(segment),
+                }
+            }
+            std::path::Component::RootDir | std::path::Component::Prefix(_) => {
+                return Err(invalid_path(
+                    "it's an absolute path, rather than a path within the assets",
+                ));
+            }
+        }
+    }
+
+    Ok(AssetPath::from(resolved).with_source(source))
+}
+
+/// A file used by a map, read ahead of loading the map, or the error from reading it.
+type MapFile = (PathBuf, Result<Arc<[u8]>, (ErrorKind, String)>);
+
+/// Reads the external tilesets (`.tsx`) and object templates (`.tx`) used by a map through the
+/// asset system, along with the ones they use in turn.
+///
+/// `tiled` reads files synchronously, so they're all read here before the map is parsed. Every
+/// file is a dependency of the map, so the map is reloaded when any of them changes.
+async fn read_map_files(
+    load_context: &mut LoadContext<'_>,
+    map_path: &Path,
+    map_bytes: &[u8],
+) -> Vec<MapFile> {
+    let mut files: Vec<MapFile> = Vec::new();
+    let mut pending = referenced_files(map_path, map_bytes);
+    while let Some(path) = pending.pop() {
+        if files.iter().any(|(file_path, _)| *file_path == path) {
+            continue;
+        }
+
+        // Invalid paths are reported by `TiledLoaderError::from_tiled` from their error kind.
+        let bytes = match resolve_asset_path(load_context.asset_path(), &path) {
+            Ok(asset_path) => match load_context.read_asset_bytes(asset_path).await {
+                Ok(bytes) => Ok(Arc::<[u8]>::from(bytes)),
+                Err(e @ ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => {
+                    Err((ErrorKind::NotFound, e.to_string()))
+                }
+                Err(e) => Err((ErrorKind::Other, e.to_string())),
+            },
+            Err(TiledLoaderError::InvalidPath { reason, .. }) => {
+                Err((ErrorKind::InvalidInput, reason))
+            }
+            Err(e) => Err((ErrorKind::Other, e.to_string())),
+        };
+
+        if let Ok(bytes) = &bytes {
+            pending.extend(referenced_files(&path, bytes));
         }
+        files.push((path, bytes));
     }
+    files
 }
 
-impl tiled::ResourceReader for BytesResourceReader {
-    type Resource = Cursor<Arc<[u8]>>;
+/// Finds the external tilesets and object templates used by a `Tiled` file, with their paths
+/// joined onto the file's directory like `tiled` does.
+///
+/// Files that aren't valid XML are left for `tiled` to report.
+fn referenced_files(path: &Path, bytes: &[u8]) -> Vec<PathBuf> {
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut files = Vec::new();
+    for event in xml::reader::EventReader::new(bytes) {
+        let Ok(event) = event else {
+            break;
+        };
+        let xml::reader::XmlEvent::StartElement {
+            name, attributes, ..
+        } = event
+        else {
+            continue;
+        };
+
+        let attribute = match name.local_name.as_str() {
+            "tileset" => "source",
+            "object" => "template",
+            _ => continue,
+        };
+        if let Some(attribute) = attributes
+            .iter()
+            .find(|attribute_data| attribute_data.name.local_name == attribute)
+        {
+            files.push(directory.join(&attribute.value));
+        }
+    }
+    files
+}
+
+/// Reads the files of a map for `tiled` from the ones read ahead by `read_map_files`.
+struct TiledResourceReader<'a> {
+    /// The path of the map, as given to `tiled`.
+    map_path: PathBuf,
+    map_bytes: Arc<[u8]>,
+    files: &'a [MapFile],
+
+    /// How many bytes of the map `tiled` has read, to locate the errors it reports.
+    map_read: &'a Cell<usize>,
+}
+
+impl<'a> tiled::ResourceReader for TiledResourceReader<'a> {
+    type Resource = TiledFile<'a>;
     type Error = std::io::Error;
 
-    fn read_from(&mut self, _path: &Path) -> std::result::Result<Self::Resource, Self::Error> {
-        // In this case, the path is ignored because the byte data is already provided.
-        Ok(Cursor::new(self.bytes.clone()))
+    fn read_from(&mut self, path: &Path) -> std::result::Result<Self::Resource, Self::Error> {
+        if path == self.map_path {
+            return Ok(TiledFile {
+                cursor: Cursor::new(self.map_bytes.clone()),
+                read: Some(self.map_read),
+            });
+        }
+
+        match self.files.iter().find(|(file_path, _)| file_path == path) {
+            Some((_, Ok(bytes))) => Ok(TiledFile {
+                cursor: Cursor::new(bytes.clone()),
+                read: None,
+            }),
+            Some((_, Err((kind, message)))) => Err(std::io::Error::new(*kind, message.clone())),
+            None => Err(std::io::Error::other(format!(
+                "'{}' wasn't read along with the map",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// A file given to `tiled` by the `TiledResourceReader`.
+struct TiledFile<'a> {
+    cursor: Cursor<Arc<[u8]>>,
+
+    /// Where to keep how many bytes of the file have been read, if that's needed.
+    read: Option<&'a Cell<usize>>,
+}
+
+impl Read for TiledFile<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.cursor.read(buf)?;
+        if let Some(read) = self.read {
+            read.set(self.cursor.position() as usize);
+        }
+        Ok(count)
+    }
+}
+
+/// Loads the `Tiled` project set in the settings of a map, if it has one.
+///
+/// The project is a dependency of the map, so the map is reloaded when the project changes.
+async fn load_project(
+    load_context: &mut LoadContext<'_>,
+    settings: &TiledLoaderSettings,
+) -> Result<Option<TiledProject>, TiledLoaderError> {
+    let Some(project_path) = &settings.project else {
+        return Ok(None);
+    };
+
+    let project = load_context
+        .loader()
+        .immediate()
+        .load::<TiledProject>(project_path.as_str())
+        .await
+        .map_err(|error| match error {
+            LoadDirectError::LoadError {
+                error: AssetLoadError::AssetLoaderError(_),
+                ..
+            } => TiledLoaderError::Parse {
+                path: project_path.into(),
+                line: None,
+                element: None,
+                message: error.to_string(),
+            },
+            error => std::io::Error::other(error).into(),
+        })?;
+    Ok(Some(project.take()))
+}
+
+/// Settings for how the `TiledLoader` imports a map.
+///
+/// These can be set for each map in its `.meta` file, or with `AssetServer::load_with_settings`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TiledLoaderSettings {
+    /// The scale of the map, on top of the scale of its transform.
+    pub scale: f32,
+
+    /// The names of the layers to spawn. Every layer is spawned if this is empty.
+    pub include_layers: Vec<String>,
+
+    /// The names of the layers to skip, even if they're in `include_layers`.
+    pub exclude_layers: Vec<String>,
+
+    /// Whether colliders are created from tile collisions and objects with a `collider_type`.
+    ///
+    /// Zones always have a collider, since they can't be entered without one, while tile layers
+    /// with a parallax factor never have any.
+    pub generate_colliders: bool,
+
+    /// The rigid body of tile objects without a `collider_type` property.
+    pub default_rigid_body: TiledRigidBody,
+
+    /// The distance along the z-axis between consecutive layers.
+    ///
+    /// Layers are drawn below the player unless they have the `render_above_player` or `z`
+    /// properties, so this should leave room for every layer of the map.
+    pub layer_z_spacing: f32,
+
+    /// The sampler (such as nearest or linear filtering) of the map's images.
+    ///
+    /// `ImageSampler::Default` uses the sampler of the `ImagePlugin`.
+    pub sampler: ImageSampler,
+
+    /// The asset path of the `Tiled` project (`.tiled-project`) whose property types the map's
+    /// custom properties are checked against. Maps without a project aren't checked.
+    pub project: Option<String>,
+}
+
+impl Default for TiledLoaderSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            include_layers: Vec::new(),
+            exclude_layers: Vec::new(),
+            generate_colliders: true,
+            default_rigid_body: TiledRigidBody::default(),
+            layer_z_spacing: 1.0,
+            sampler: ImageSampler::Default,
+            project: None,
+        }
+    }
+}
+
+impl TiledLoaderSettings {
+    /// Checks whether the layer with the given name should be spawned.
+    pub fn includes_layer(&self, name: &str) -> bool {
+        (self.include_layers.is_empty() || self.include_layers.iter().any(|layer| layer == name))
+            && !self.exclude_layers.iter().any(|layer| layer == name)
+    }
+
+    /// Loads one of the map's images with the configured sampler.
+    fn load_image(
+        &self,
+        load_context: &mut LoadContext<'_>,
+        path: AssetPath<'static>,
+    ) -> Handle<Image> {
+        if matches!(self.sampler, ImageSampler::Default) {
+            return load_context.load(path);
+        }
+
+        let sampler = self.sampler.clone();
+        load_context
+            .loader()
+            .with_settings(move |settings: &mut ImageLoaderSettings| {
+                settings.sampler = sampler.clone();
+            })
+            .load(path)
     }
 }
 
 /// Loads and deserializes a map from `Tiled` (or any map with the `.tmx` extension).
+///
+/// If the map's settings name a `Tiled` project, its custom properties are checked against the
+/// project's property types, and the map fails to load with `TiledLoaderError::InvalidProperty`
+/// if they don't match.
 pub struct TiledLoader;
 
 impl AssetLoader for TiledLoader {
     type Asset = TiledMap;
-    type Settings = ();
-    type Error = String;
+    type Settings = TiledLoaderSettings;
+    type Error = TiledLoaderError;
 
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
-        load_context: &mut bevy::asset::LoadContext<'_>,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
-        reader
-            .read_to_end(&mut bytes)
-            .await
-            .map_err(|e| e.to_string())?;
+        reader.read_to_end(&mut bytes).await?;
 
-        let mut loader = tiled::Loader::with_cache_and_reader(
+        let map_path = load_context.path().to_path_buf();
+        let files = read_map_files(load_context, &map_path, &bytes).await;
+        let map_read = Cell::new(0);
+        let map = tiled::Loader::with_cache_and_reader(
             tiled::DefaultResourceCache::new(),
-            BytesResourceReader::new(&bytes),
-        );
+            TiledResourceReader {
+                map_path: map_path.clone(),
+                map_bytes: Arc::from(bytes.as_slice()),
+                files: &files,
+                map_read: &map_read,
+            },
+        )
+        .load_tmx_map(&map_path)
+        .map_err(|e| TiledLoaderError::from_tiled(map_path.clone(), &bytes, map_read.get(), e))?;
+
+        if let Some(project) = load_project(load_context, settings).await? {
+            let to_parse_error = |e: xml::reader::Error| TiledLoaderError::Parse {
+                path: map_path.clone(),
+                line: Some(e.position().row + 1),
+                element: None,
+                message: e.to_string(),
+            };
 
-        let map = loader
-            .load_tmx_map(load_context.path())
-            .map_err(|e| {
-                std::io::Error::new(ErrorKind::Other, format!("Could not load TMX map: {e}"))
-            })
-            .map_err(|e| e.to_string())?;
+            // External tilesets and templates are checked along with the map.
+            let mut errors = project.validate(&bytes).map_err(to_parse_error)?;
+            for file_bytes in files.iter().filter_map(|(_, bytes)| bytes.as_ref().ok()) {
+                errors.extend(project.validate(file_bytes).map_err(to_parse_error)?);
+            }
+            if !errors.is_empty() {
+                return Err(InvalidProperties {
+                    map: map_path.display().to_string(),
+                    errors,
+                }
+                .into());
+            }
+        }
 
         let mut tilemap_textures = HashMap::default();
+        let mut tile_sizes = HashMap::default();
+        let mut tile_image_offsets = HashMap::default();
+        let mut tile_sprites = HashMap::default();
+        let mut tile_atlas_layouts = HashMap::default();
 
         for (tileset_index, tileset) in map.tilesets().iter().enumerate() {
-            let tilemap_texture = match &tileset.image {
+            let (tilemap_texture, tile_size) = match &tileset.image {
                 None => {
-                    info!(
-                            "Skipping image collection tileset '{}' which is incompatible with atlas feature",
+                    // Every image in a `TilemapTexture::Vector` must be the same size, so the most
+                    // common tile size is used for the tilemap and any other tiles are drawn as
+                    // individual sprites instead.
+                    let mut size_counts = HashMap::<(i32, i32), usize>::default();
+                    for (_, tile) in tileset.tiles() {
+                        if let Some(img) = &tile.image {
+                            *size_counts.entry((img.width, img.height)).or_default() += 1;
+                        }
+                    }
+                    let Some((tile_width, tile_height)) = size_counts
+                        .into_iter()
+                        .max_by_key(|(size, count)| (*count, *size))
+                        .map(|(size, _)| size)
+                    else {
+                        info!(
+                            "Skipping image collection tileset '{}' with no images",
                             tileset.name
                         );
-                    continue;
+                        continue;
+                    };
+
+                    let mut tile_images = Vec::new();
+                    for (tile_id, tile) in tileset.tiles() {
+                        let Some(img) = &tile.image else {
+                            continue;
+                        };
+
+                        let texture = settings.load_image(
+                            load_context,
+                            resolve_asset_path(load_context.asset_path(), &img.source)?,
+                        );
+
+                        if (img.width, img.height) == (tile_width, tile_height) {
+                            tile_image_offsets
+                                .insert((tileset_index, tile_id), tile_images.len() as u32);
+                            tile_images.push(texture);
+                        } else {
+                            tile_sprites.insert(
+                                (tileset_index, tile_id),
+                                TileSprite {
+                                    image: texture,
+                                    size: Vec2::new(img.width as f32, img.height as f32),
+                                },
+                            );
+                        }
+                    }
+
+                    (
+                        TilemapTexture::Vector(tile_images),
+                        TilemapTileSize {
+                            x: tile_width as f32,
+                            y: tile_height as f32,
+                        },
+                    )
                 }
 
                 Some(img) => {
-                    // The load context path is the TMX file itself. If the file is at the root of the
-                    // assets/ directory structure then the tmx_dir will be empty, which is fine.
-                    let tile_path = img
-                        .source
-                        .to_str()
-                        .expect("The asset load context was empty.");
-                    let asset_path = AssetPath::from(tile_path);
-                    let texture: Handle<Image> = load_context.load(asset_path.clone());
-
-                    TilemapTexture::Single(texture.clone())
+                    let texture = settings.load_image(
+                        load_context,
+                        resolve_asset_path(load_context.asset_path(), &img.source)?,
+                    );
+
+                    // Used to draw tiles from the tileset as sprites, for objects that aren't
+                    // placed on the tile grid.
+                    let layout = TextureAtlasLayout::from_grid(
+                        UVec2::new(tileset.tile_width, tileset.tile_height),
+                        tileset.columns,
+                        tileset.tilecount.div_ceil(tileset.columns.max(1)),
+                        Some(UVec2::splat(tileset.spacing)),
+                        Some(UVec2::splat(tileset.margin)),
+                    );
+                    tile_atlas_layouts.insert(
+                        tileset_index,
+                        load_context
+                            .add_labeled_asset(format!("tileset{tileset_index}_atlas"), layout),
+                    );
+
+                    (
+                        TilemapTexture::Single(texture.clone()),
+                        TilemapTileSize {
+                            x: tileset.tile_width as f32,
+                            y: tileset.tile_height as f32,
+                        },
+                    )
                 }
             };
 
             tilemap_textures.insert(tileset_index, tilemap_texture);
+            tile_sizes.insert(tileset_index, tile_size);
         }
 
+        // Image layers can be nested within groups, like any other layer.
+        let mut image_layers = HashMap::default();
+        for layer in flatten_layers(map.layers()) {
+            let tiled::LayerType::Image(image_layer) = layer.layer_type() else {
+                continue;
+            };
+            let Some(img) = &image_layer.image else {
+                continue;
+            };
+            image_layers.insert(
+                layer.id(),
+                settings.load_image(
+                    load_context,
+                    resolve_asset_path(load_context.asset_path(), &img.source)?,
+                ),
+            );
+        }
+
+        let image_layer_repeats = image_layer_repeats(&bytes);
+        let (tile_origin, map_size) = tile_extents(&map);
+
         let asset_map = TiledMap {
             map,
             tilemap_textures,
+            tile_sizes,
+            tile_image_offsets,
+            tile_sprites,
+            tile_atlas_layouts,
+            image_layers,
+            image_layer_repeats,
+            map_size,
+            tile_origin,
+            settings: settings.clone(),
         };
 
         info!("Loaded map: {}", load_context.path().display());
@@ -165,17 +1550,34 @@ impl AssetLoader for TiledLoader {
     }
 }
 
+/// The components of a map entity needed to spawn its layers.
+type MapComponents<'a> = (
+    Entity,
+    &'a TiledMapHandle,
+    &'a Transform,
+    &'a mut TiledLayersStorage,
+    &'a TilemapRenderSettings,
+);
+
+/// A map whose layers are being spawned, along with what spawning them needs.
+struct SpawningMap<'a> {
+    tiled_map: &'a TiledMap,
+
+    /// The asset path of the map, to tell which map warnings are about.
+    path: &'a str,
+
+    transform: &'a Transform,
+    render_settings: &'a TilemapRenderSettings,
+    class_registry: &'a TiledClassRegistry,
+}
+
 pub fn process_loaded_maps(
     mut commands: Commands,
     mut map_events: EventReader<AssetEvent<TiledMap>>,
     maps: Res<Assets<TiledMap>>,
-    tile_storage_query: Query<(Entity, &TileStorage)>,
-    mut map_query: Query<(
-        &TiledMapHandle,
-        &mut TiledLayersStorage,
-        &TilemapRenderSettings,
-    )>,
+    mut map_query: Query<MapComponents, Without<RemoveMap>>,
     new_maps: Query<&TiledMapHandle, Added<TiledMapHandle>>,
+    class_registry: Res<TiledClassRegistry>,
 ) {
     let mut changed_maps = Vec::<AssetId<TiledMap>>::default();
     for event in map_events.read() {
@@ -204,17 +1606,129 @@ pub fn process_loaded_maps(
     }
 
     for changed_map in changed_maps.iter() {
-        for (map_handle, mut layer_storage, render_settings) in map_query.iter_mut() {
+        for (map_entity, map_handle, map_transform, mut layer_storage, render_settings) in
+            map_query.iter_mut()
+        {
             // only deal with currently changed map
             if map_handle.0.id() != *changed_map {
                 continue;
             }
-            if let Some(tiled_map) = maps.get(&map_handle.0) {
-                // TODO: Create a RemoveMap component..
-                for layer_entity in layer_storage.storage.values() {
-                    if let Ok((_, layer_tile_storage)) = tile_storage_query.get(*layer_entity) {
-                        for tile in layer_tile_storage.iter().flatten() {
-                            commands.entity(*tile).despawn()
+            let Some(tiled_map) = maps.get(&map_handle.0) else {
+                continue;
+            };
+
+            // Every layer, object and tile of the map is spawned within it, so the old ones are
+            // despawned before the map is spawned again.
+            commands.entity(map_entity).despawn_related::<Children>();
+            layer_storage.clear();
+
+            let map_path = map_handle
+                .0
+                .path()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+
+            let settings = &tiled_map.settings;
+            let mut layers = Vec::new();
+            collect_layers(
+                &mut commands,
+                &mut layer_storage,
+                settings,
+                &map_path,
+                tiled_map.map.layers(),
+                LayerGroup::map(map_entity),
+                &mut layers,
+            );
+
+            let map = SpawningMap {
+                tiled_map,
+                path: &map_path,
+                transform: map_transform,
+                render_settings,
+                class_registry: &class_registry,
+            };
+
+            // Objects aren't limited to the tile grid, so they're spawned within an entity of their
+            // own for each object layer.
+            let mut spawn_points = HashMap::default();
+            for map_layer in &layers {
+                if let Some(object_layer_entity) =
+                    spawn_object_layer(&mut commands, &map, map_layer, &mut spawn_points)
+                {
+                    layer_storage.insert_layer(
+                        map_layer.index,
+                        &map_layer.layer.name,
+                        object_layer_entity,
+                    );
+                }
+            }
+
+            // Image layers are drawn by a single sprite each.
+            for map_layer in &layers {
+                if let Some(image_layer_entity) =
+                    spawn_image_layer(&mut commands, tiled_map, map_layer, map_transform)
+                {
+                    if let Some(parallax) = TiledParallax::new(map_layer.parallax, map_entity) {
+                        commands.entity(image_layer_entity).insert(parallax);
+                    }
+                    layer_storage.insert_layer(
+                        map_layer.index,
+                        &map_layer.layer.name,
+                        image_layer_entity,
+                    );
+                }
+            }
+
+            // The bounds of the map cover every tile layer it spawns.
+            let mut map_bounds = None::<Bounds>;
+
+            for map_layer in &layers {
+                let tiled::LayerType::Tiles(tile_layer) = map_layer.layer.layer_type() else {
+                    continue;
+                };
+
+                // Layers are drawn at their own depth, which isn't scaled by the map.
+                let center = Vec2::new(map_layer.offset.x, -map_layer.offset.y) * settings.scale;
+                let layer_entity = commands
+                    .spawn((
+                        TiledTileLayer {
+                            id: map_layer.layer.id(),
+                        },
+                        Transform::from_translation(
+                            center.extend(map_layer.z / map_transform.scale.z),
+                        )
+                        .with_scale(Vec3::splat(settings.scale)),
+                        map_layer.visibility(),
+                        ChildOf(map_layer.parent),
+                    ))
+                    .id();
+                if let Some(parallax) = TiledParallax::new(map_layer.parallax, map_entity) {
+                    commands.entity(layer_entity).insert(parallax);
+                }
+                layer_storage.insert_layer(map_layer.index, &map_layer.layer.name, layer_entity);
+
+                let layer_bounds = Bounds::from_center_half_size(
+                    map_transform.transform_point(center.extend(0.0)).truncate(),
+                    tiled_map.grid().size() / 2.0 * settings.scale * map_transform.scale.truncate(),
+                );
+                map_bounds = Some(match map_bounds {
+                    Some(bounds) => bounds.union(layer_bounds),
+                    None => layer_bounds,
+                });
+
+                // Infinite layers are all placed within the extents of the map's occupied chunks,
+                // so that the layers line up with each other.
+                let map_size = tiled_map.map_size;
+                let tile_origin = tiled_map.tile_origin;
+
+                // Only the tilesets the layer uses get a tilemap.
+                let mut used_tilesets = HashSet::new();
+                for x in 0..map_size.x as i32 {
+                    for y in 0..map_size.y as i32 {
+                        if let Some(layer_tile) =
+                            tile_layer.get_tile(tile_origin.x + x, tile_origin.y + y)
+                        {
+                            used_tilesets.insert(layer_tile.tileset_index());
                         }
                     }
                 }
@@ -223,331 +1737,899 @@ pub fn process_loaded_maps(
                 // tiled texture or from a Vec of independent per-tile images. Furthermore, all of
                 // the per-tile images must be the same size. Since Tiled allows tiles of mixed
                 // tilesets on each layer and allows differently-sized tile images in each tileset,
-                // this means we need to load each combination of tileset and layer separately.
-                for (tileset_index, tileset) in tiled_map.map.tilesets().iter().enumerate() {
-                    let Some(tilemap_texture) = tiled_map.tilemap_textures.get(&tileset_index)
-                    else {
-                        warn!("Skipped creating layer with missing tilemap textures.");
+                // this means we need a tilemap for each tileset the layer uses.
+                for tileset_index in 0..tiled_map.map.tilesets().len() {
+                    if !used_tilesets.contains(&tileset_index) {
                         continue;
-                    };
+                    }
 
-                    let tile_size = TilemapTileSize {
-                        x: tileset.tile_width as f32,
-                        y: tileset.tile_height as f32,
-                    };
+                    if let Some(tilemap_entity) = spawn_tile_layer(
+                        &mut commands,
+                        &map,
+                        map_layer,
+                        &tile_layer,
+                        layer_entity,
+                        tileset_index,
+                    ) {
+                        layer_storage
+                            .tilemaps
+                            .insert((map_layer.index as u32, tileset_index), tilemap_entity);
+                    }
+                }
+            }
 
-                    let tile_spacing = TilemapSpacing {
-                        x: tileset.spacing as f32,
-                        y: tileset.spacing as f32,
-                    };
+            // Maps without tile layers still cover their grid.
+            let map_bounds = map_bounds.unwrap_or_else(|| {
+                Bounds::from_center_half_size(
+                    map_transform.translation.truncate(),
+                    tiled_map.grid().size() / 2.0 * settings.scale * map_transform.scale.truncate(),
+                )
+            });
+            commands
+                .entity(map_entity)
+                .insert((map_bounds, MapSpawnPoints(spawn_points)));
+        }
+    }
+}
 
-                    // Once materials have been created/added we need to then create the layers.
-                    for (layer_index, layer) in tiled_map.map.layers().enumerate() {
-                        let offset_x = layer.offset_x;
-                        let offset_y = layer.offset_y;
-
-                        let layer_data = {
-                            match layer.layer_type() {
-                                tiled::LayerType::Tiles(tile_layer) => {
-                                    if let tiled::TileLayer::Finite(layer_data) = tile_layer {
-                                        (Some(layer_data), None)
-                                    } else {
-                                        info!(
-                                    "Skipping layer {} because only finite layers are supported.",
-                                    layer.id()
-                                );
-                                        continue;
-                                    }
-                                }
-
-                                tiled::LayerType::Objects(object_layer) => {
-                                    (None, Some(object_layer))
-                                }
-
-                                _ => {
-                                    info!(
-                                "Skipping layer {} because only tile and object layers are supported.",
-                                layer.id()
-                            );
-                                    continue;
-                                }
-                            }
-                        };
+/// Spawns the objects of an object layer within an entity for the layer, if it's an object layer.
+///
+/// Spawn points are collected into `spawn_points` by their name, rather than spawned.
+fn spawn_object_layer(
+    commands: &mut Commands,
+    map: &SpawningMap,
+    map_layer: &MapLayer,
+    spawn_points: &mut HashMap<String, Vec2>,
+) -> Option<Entity> {
+    let tiled::LayerType::Objects(object_layer) = map_layer.layer.layer_type() else {
+        return None;
+    };
+    let object_layer_entity = commands
+        .spawn((
+            TiledObjectLayer {
+                id: map_layer.layer.id(),
+            },
+            Transform::default(),
+            map_layer.visibility(),
+            ChildOf(map_layer.parent),
+        ))
+        .id();
+
+    for object_data in object_layer.object_data() {
+        if object_data.user_type == "SpawnPoint" {
+            let position = map.transform.transform_point(
+                map.tiled_map
+                    .to_map_position(map_layer.offset, object_data.x, object_data.y)
+                    .extend(0.0),
+            );
+            // Falls back to the object's own name if the `name` member isn't set.
+            let name = object_property(map.path, object_data, "name")
+                .or_else(|| (!object_data.name.is_empty()).then(|| object_data.name.clone()))
+                .unwrap_or_else(|| "default".into());
+            spawn_points.insert(name, position.truncate());
+            continue;
+        }
 
-                        let map_size = TilemapSize {
-                            x: tiled_map.map.width,
-                            y: tiled_map.map.height,
-                        };
+        spawn_object(commands, map, map_layer, object_layer_entity, object_data);
+    }
+    Some(object_layer_entity)
+}
 
-                        let grid_size = TilemapGridSize {
-                            x: tiled_map.map.tile_width as f32,
-                            y: tiled_map.map.tile_height as f32,
-                        };
+/// Spawns an object drawn as a tile, or with a collider or a zone.
+///
+/// Other objects are only spawned if they're of a registered class, so the class's components
+/// have an entity to be inserted on.
+fn spawn_object(
+    commands: &mut Commands,
+    map: &SpawningMap,
+    map_layer: &MapLayer,
+    object_layer_entity: Entity,
+    object_data: &tiled::ObjectData,
+) {
+    let settings = &map.tiled_map.settings;
+    let position = map
+        .tiled_map
+        .to_map_position(map_layer.offset, object_data.x, object_data.y);
+
+    // Tiled rotates clockwise around the object's origin.
+    let transform = Transform::from_translation(position.extend(0.0))
+        .with_rotation(Quat::from_rotation_z(-object_data.rotation.to_radians()))
+        .with_scale(Vec3::splat(settings.scale));
+
+    if let Some(object_tile_data) = object_data.tile_data() {
+        spawn_tile_object(
+            commands,
+            map,
+            map_layer,
+            object_layer_entity,
+            object_data,
+            &object_tile_data,
+            transform,
+        );
+        return;
+    }
 
-                        let map_type = match tiled_map.map.orientation {
-                            tiled::Orientation::Hexagonal => {
-                                TilemapType::Hexagon(HexCoordSystem::Row)
-                            }
-                            tiled::Orientation::Isometric => {
-                                TilemapType::Isometric(IsoCoordSystem::Diamond)
-                            }
-                            tiled::Orientation::Staggered => {
-                                TilemapType::Isometric(IsoCoordSystem::Staggered)
-                            }
-                            tiled::Orientation::Orthogonal => TilemapType::Square,
-                        };
+    let grid = map.tiled_map.grid();
+
+    // Freeform objects that aren't drawn as tiles only need a collider.
+    let collider_type = settings
+        .generate_colliders
+        .then(|| object_property::<TiledRigidBody>(map.path, object_data, "collider_type"))
+        .flatten();
+    if let Some(collider_type) = collider_type {
+        let Some(collider) = shape_collider(&object_data.shape, Some(&grid)) else {
+            warn!(
+                "Skipping collider for object {} with an unsupported shape.",
+                object_data.id()
+            );
+            return;
+        };
 
-                        let mut tile_storage = TileStorage::empty(map_size);
-                        let layer_entity = commands.spawn_empty().id();
-
-                        for x in 0..map_size.x {
-                            for y in 0..map_size.y {
-                                // Transform TMX coords into bevy coords.
-                                let mapped_y = tiled_map.map.height - 1 - y;
-
-                                let mapped_x = x as i32;
-                                let mapped_y = mapped_y as i32;
-
-                                // Handles the tile layer
-                                let mut handle_tile_layer = |layer_data: tiled::FiniteTileLayer<
-                                    '_,
-                                >| {
-                                    if let Some(layer_tile) =
-                                        layer_data.get_tile(mapped_x, mapped_y)
-                                    {
-                                        if tileset_index != layer_tile.tileset_index() {
-                                            return false;
-                                        }
-
-                                        let layer_tile_data =
-                                            match layer_data.get_tile_data(mapped_x, mapped_y) {
-                                                Some(d) => d,
-                                                None => {
-                                                    return false;
-                                                }
-                                            };
-
-                                        let texture_index = match tilemap_texture {
-                                            TilemapTexture::Single(_) => layer_tile.id(),
-                                            _ => unreachable!(),
-                                        };
-
-                                        let tile_pos = TilePos { x, y };
-
-                                        let tile_entity = commands
-                                            .spawn(TileBundle {
-                                                position: tile_pos,
-                                                tilemap_id: TilemapId(layer_entity),
-                                                texture_index: TileTextureIndex(texture_index),
-                                                flip: TileFlip {
-                                                    x: layer_tile_data.flip_h,
-                                                    y: layer_tile_data.flip_v,
-                                                    d: layer_tile_data.flip_d,
-                                                },
-                                                ..Default::default()
-                                            })
-                                            .id();
-
-                                        tile_storage.set(&tile_pos, tile_entity);
-                                        true
-                                    } else {
-                                        true
-                                    }
-                                };
-
-                                // Handles the object layer
-                                let handle_object_layer = |object_data: &tiled::ObjectData| {
-                                    let object_tile_data = match object_data.tile_data() {
-                                        Some(d) => d,
-                                        None => {
-                                            return None;
-                                        }
-                                    };
-
-                                    let texture_index = match tilemap_texture {
-                                        TilemapTexture::Single(_) => object_tile_data.id(),
-                                        _ => unreachable!(),
-                                    };
-
-                                    // Transform TMX coords into bevy coords.
-                                    let (x, y) = {
-                                        let x = (object_data.x / tiled_map.map.width as f32) as u32;
-                                        let y =
-                                            (object_data.y / tiled_map.map.height as f32) as u32;
-                                        (x, y)
-                                    };
-                                    let tile_pos = TilePos { x, y };
-
-                                    let collider_type = if let Some(collider_type_value) =
-                                        object_data.properties.get("collider_type")
-                                    {
-                                        if let tiled::PropertyValue::StringValue(
-                                            collider_type_string,
-                                        ) = collider_type_value
-                                        {
-                                            Some(match collider_type_string.as_str() {
-                                                "Dynamic" => RigidBody::Dynamic,
-                                                "Static" => RigidBody::Static,
-                                                "Kinematic" => RigidBody::Kinematic,
-                                                _ => unreachable!(),
-                                            })
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        None
-                                    };
-
-                                    let hitbox = {
-                                        let raw_hitbox = if let Some(hitbox_value) =
-                                            object_data.properties.get("hitbox")
-                                        {
-                                            if let tiled::PropertyValue::ClassValue {
-                                                properties,
-                                                ..
-                                            } = hitbox_value
-                                            {
-                                                let width = if let Some(width) =
-                                                    properties.get("width")
-                                                {
-                                                    if let tiled::PropertyValue::FloatValue(width) =
-                                                        width
-                                                    {
-                                                        Some(width)
-                                                    } else {
-                                                        None
-                                                    }
-                                                } else {
-                                                    None
-                                                };
-
-                                                let height = if let Some(height) =
-                                                    properties.get("height")
-                                                {
-                                                    if let tiled::PropertyValue::FloatValue(
-                                                        height,
-                                                    ) = height
-                                                    {
-                                                        Some(height)
-                                                    } else {
-                                                        None
-                                                    }
-                                                } else {
-                                                    None
-                                                };
-
-                                                (width, height)
-                                            } else {
-                                                (None, None)
-                                            }
-                                        } else {
-                                            (None, None)
-                                        };
-
-                                        let (width, height) = raw_hitbox;
-                                        if width.is_some() && height.is_some() {
-                                            (width.unwrap(), height.unwrap())
-                                        } else if width.is_some() && height.is_none() {
-                                            (width.unwrap(), width.unwrap())
-                                        } else if width.is_none() && height.is_some() {
-                                            (height.unwrap(), height.unwrap())
-                                        } else {
-                                            (
-                                                &(tileset.tile_width as f32),
-                                                &(tileset.tile_height as f32),
-                                            )
-                                        }
-                                    };
-
-                                    let tile_entity = (
-                                        TileBundle {
-                                            position: tile_pos,
-                                            tilemap_id: TilemapId(layer_entity),
-                                            texture_index: TileTextureIndex(texture_index),
-                                            flip: TileFlip {
-                                                x: object_tile_data.flip_h,
-                                                y: object_tile_data.flip_v,
-                                                d: object_tile_data.flip_d,
-                                            },
-                                            ..Default::default()
-                                        },
-                                        TiledColliderObject,
-                                        collider_type.unwrap_or_else(|| RigidBody::Static),
-                                        Collider::rectangle(*hitbox.0, *hitbox.1),
-                                    );
-
-                                    Some((tile_pos, tile_entity))
-                                };
-
-                                match layer_data {
-                                    (Some(tile_layer), None) => {
-                                        if !handle_tile_layer(tile_layer) {
-                                            continue;
-                                        }
-                                    }
-
-                                    (None, Some(object_layer)) => {
-                                        let objects = object_layer.object_data();
-                                        for object_data in objects {
-                                            if let Some((tile_pos, tile_entity)) =
-                                                handle_object_layer(object_data)
-                                            {
-                                                let tile_entity = commands.spawn(tile_entity).id();
-                                                tile_storage.set(&tile_pos, tile_entity);
-                                            }
-                                        }
-                                    }
-
-                                    (Some(tile_layer), Some(object_layer)) => {
-                                        // Handle tile layer
-                                        if !handle_tile_layer(tile_layer) {
-                                            continue;
-                                        }
-
-                                        // Handle object layer
-                                        {
-                                            let objects = object_layer.object_data();
-                                            for object_data in objects {
-                                                if let Some((tile_pos, tile_entity)) =
-                                                    handle_object_layer(object_data)
-                                                {
-                                                    let tile_entity =
-                                                        commands.spawn(tile_entity).id();
-                                                    tile_storage.set(&tile_pos, tile_entity);
-                                                }
-                                            }
-                                        }
-                                    }
-
-                                    (None, None) => continue,
-                                }
-                            }
-                        }
+        let mut object_entity = commands.spawn((
+            TiledObject {
+                id: object_data.id(),
+            },
+            TiledColliderObject,
+            RigidBody::from(collider_type),
+            collider,
+            transform,
+            ChildOf(object_layer_entity),
+        ));
+        insert_object_classes(
+            map.class_registry,
+            &mut object_entity,
+            map.path,
+            object_data,
+            None,
+        );
+        return;
+    }
 
-                        let transform =
-                            Transform::from_xyz(offset_x, -offset_y, layer_index as f32)
-                                .with_scale(Vec3::splat(MAP_SCALE));
-
-                        commands.entity(layer_entity).insert(TilemapBundle {
-                            grid_size,
-                            size: map_size,
-                            storage: tile_storage,
-                            texture: tilemap_texture.clone(),
-                            tile_size,
-                            spacing: tile_spacing,
-                            anchor: TilemapAnchor::Center,
-                            transform,
-                            map_type,
-                            render_settings: *render_settings,
-                            ..Default::default()
-                        });
+    let Some(zone) = object_property::<Zone>(map.path, object_data, "zone") else {
+        if map.class_registry.contains(&object_data.user_type) {
+            let mut object_entity = commands.spawn((
+                TiledObject {
+                    id: object_data.id(),
+                },
+                transform,
+                ChildOf(object_layer_entity),
+            ));
+            insert_object_classes(
+                map.class_registry,
+                &mut object_entity,
+                map.path,
+                object_data,
+                None,
+            );
+        }
+        return;
+    };
 
-                        layer_storage
-                            .storage
-                            .insert(layer_index as u32, layer_entity);
+    let tiled::ObjectShape::Rect { .. } = object_data.shape else {
+        warn!(
+            "Skipping zone '{}' because only rectangular zones are supported.",
+            object_data.name
+        );
+        return;
+    };
+    let Some(collider) = shape_collider(&object_data.shape, Some(&grid)) else {
+        return;
+    };
+
+    let mut object_entity = commands.spawn((
+        TiledObject {
+            id: object_data.id(),
+        },
+        zone,
+        Sensor,
+        collider,
+        CollisionEventsEnabled,
+        transform,
+        ChildOf(object_layer_entity),
+    ));
+    insert_object_classes(
+        map.class_registry,
+        &mut object_entity,
+        map.path,
+        object_data,
+        None,
+    );
+}
+
+/// Spawns an object drawn as a tile, with a hitbox covering the tile if colliders are generated.
+///
+/// The `transform` places the object on the map, and is drawn at the depth of its layer.
+fn spawn_tile_object(
+    commands: &mut Commands,
+    map: &SpawningMap,
+    map_layer: &MapLayer,
+    object_layer_entity: Entity,
+    object_data: &tiled::ObjectData,
+    object_tile_data: &tiled::ObjectTileData,
+    transform: Transform,
+) {
+    let tiled_map = map.tiled_map;
+    let settings = &tiled_map.settings;
+    let tiled::TilesetLocation::Map(tileset_index) = *object_tile_data.tileset_location() else {
+        warn!(
+            "Skipping tile object {} from a template tileset.",
+            object_data.id()
+        );
+        return;
+    };
+
+    let Some(mut sprite) = object_tile_sprite(tiled_map, tileset_index, object_tile_data.id())
+    else {
+        warn!(
+            "Skipping tile object {} with missing tile textures.",
+            object_data.id()
+        );
+        return;
+    };
+
+    let tileset = &tiled_map.map.tilesets()[tileset_index];
+    let size = match object_data.shape {
+        tiled::ObjectShape::Rect { width, height } => Vec2::new(width, height),
+        _ => Vec2::new(tileset.tile_width as f32, tileset.tile_height as f32),
+    };
+
+    // Tile objects are drawn from their bottom-left corner, or their bottom-center on isometric
+    // maps.
+    let (anchor, hitbox_center) = if tiled_map.grid().is_isometric() {
+        (Anchor::BottomCenter, Vec2::new(0.0, size.y / 2.0))
+    } else {
+        (Anchor::BottomLeft, size / 2.0)
+    };
+    sprite.custom_size = Some(size);
+    sprite.anchor = anchor;
+    sprite.flip_x = object_tile_data.flip_h;
+    sprite.flip_y = object_tile_data.flip_v;
+    sprite.color = map_layer.color();
+
+    let (hitbox_width, hitbox_height) = object_property::<Hitbox>(map.path, object_data, "hitbox")
+        .and_then(|hitbox| hitbox.size())
+        .unwrap_or((size.x, size.y));
+
+    // Objects are drawn at the same depth as their layer, which isn't scaled by the map.
+    let mut transform = transform;
+    transform.translation.z = map_layer.z / map.transform.scale.z;
+
+    let mut object_entity = commands.spawn((
+        sprite,
+        TiledObject {
+            id: object_data.id(),
+        },
+        transform,
+        ChildOf(object_layer_entity),
+    ));
+    if settings.generate_colliders {
+        object_entity.insert((
+            TiledColliderObject,
+            RigidBody::from(
+                object_property::<TiledRigidBody>(map.path, object_data, "collider_type")
+                    .unwrap_or(settings.default_rigid_body),
+            ),
+            // The hitbox is centered on the tile.
+            Collider::compound(vec![(
+                hitbox_center,
+                Rotation::default(),
+                Collider::rectangle(hitbox_width, hitbox_height),
+            )]),
+        ));
+    }
+    if let Some(animation) = tile_animation(tiled_map, tileset_index, object_tile_data.id()) {
+        object_entity.insert(animation);
+    }
+    insert_object_classes(
+        map.class_registry,
+        &mut object_entity,
+        map.path,
+        object_data,
+        tileset.get_tile(object_tile_data.id()).as_deref(),
+    );
+}
+
+/// Spawns a tilemap within the entity of a tile layer for the layer's tiles from one tileset,
+/// along with their colliders.
+///
+/// Returns `None` if the tileset's textures are missing.
+fn spawn_tile_layer(
+    commands: &mut Commands,
+    map: &SpawningMap,
+    map_layer: &MapLayer,
+    tile_layer: &tiled::TileLayer,
+    layer_entity: Entity,
+    tileset_index: usize,
+) -> Option<Entity> {
+    let tiled_map = map.tiled_map;
+    let settings = &tiled_map.settings;
+    let tileset = &tiled_map.map.tilesets()[tileset_index];
+    let Some(tilemap_texture) = tiled_map.tilemap_textures.get(&tileset_index) else {
+        warn!("Skipped creating layer with missing tilemap textures.");
+        return None;
+    };
+
+    let map_size = tiled_map.map_size;
+    let tile_origin = tiled_map.tile_origin;
+    let grid = tiled_map.grid();
+    let grid_size = grid.grid_size();
+    let map_type = grid.map_type();
+    let tile_size = tiled_map.tile_sizes[&tileset_index];
+    let tile_spacing = TilemapSpacing {
+        x: tileset.spacing as f32,
+        y: tileset.spacing as f32,
+    };
+    let layer_color = map_layer.color();
+
+    let mut tile_storage = TileStorage::empty(map_size);
+    let tilemap_entity = commands.spawn(ChildOf(layer_entity)).id();
+
+    // Solid tiles are merged into as few rectangles as possible, and the collision shapes of every
+    // other tile in the layer are merged into a single static body. Layers with a parallax factor
+    // move with the camera, so they don't get colliders at all.
+    let generate_colliders = settings.generate_colliders && map_layer.parallax == Vec2::ONE;
+    let can_merge_tiles = matches!(map_type, TilemapType::Square);
+    let mut tile_collisions = TileCollisions::new();
+    let mut solid_tiles = vec![false; (map_size.x * map_size.y) as usize];
+    let mut layer_colliders = Vec::new();
+
+    for x in 0..map_size.x {
+        for y in 0..map_size.y {
+            let Some(layer_tile) =
+                tile_layer.get_tile(tile_origin.x + x as i32, tile_origin.y + y as i32)
+            else {
+                continue;
+            };
+            if layer_tile.tileset_index() != tileset_index {
+                continue;
+            }
+
+            let tile_pos = grid.tile_pos(x, y);
+            let tile_key = (tileset_index, layer_tile.id());
+            let tile_sprite = tiled_map.tile_sprites.get(&tile_key);
+
+            // Tiled draws tiles from the bottom-left corner of their cell.
+            let cell_corner = tile_pos.center_in_world(
+                &map_size,
+                &grid_size,
+                &tile_size,
+                &map_type,
+                &TilemapAnchor::None,
+            ) - grid.tile_size() / 2.0;
+
+            // Collision shapes are relative to the top-left corner of the tile's image, and are
+            // flipped along with it.
+            let image_size =
+                tile_sprite.map_or(Vec2::new(tile_size.x, tile_size.y), |sprite| sprite.size);
+            let tile_top_left = cell_corner + Vec2::Y * image_size.y;
+            let flip = TileFlip {
+                x: layer_tile.flip_h,
+                y: layer_tile.flip_v,
+                d: layer_tile.flip_d,
+            };
+            let (is_solid, shapes) = tile_collisions
+                .entry((layer_tile.id(), flip.x, flip.y, flip.d))
+                .or_insert_with(|| {
+                    if !generate_colliders {
+                        (false, Vec::new())
+                    } else if can_merge_tiles
+                        && tile_sprite.is_none()
+                        && is_solid_tile(tileset, layer_tile.id(), &grid_size)
+                        && (!flip.d || image_size.x == image_size.y)
+                    {
+                        // Tiles covered by their collision stay covered when flipped, unless
+                        // swapping their axes changes their size.
+                        (true, Vec::new())
+                    } else {
+                        (
+                            false,
+                            flip_collision_shapes(
+                                collision_shapes(tileset, layer_tile.id()),
+                                image_size,
+                                flip,
+                            ),
+                        )
                     }
+                });
+            if *is_solid {
+                solid_tiles[(tile_pos.y * map_size.x + tile_pos.x) as usize] = true;
+            }
+            for (position, rotation, collider) in shapes.iter() {
+                layer_colliders.push((tile_top_left + *position, *rotation, collider.clone()));
+            }
+
+            if let Some(tile_sprite) = tile_sprite {
+                let mut tile_entity = commands.spawn((
+                    Sprite {
+                        image: tile_sprite.image.clone(),
+                        custom_size: Some(tile_sprite.size),
+                        flip_x: layer_tile.flip_h,
+                        flip_y: layer_tile.flip_v,
+                        anchor: Anchor::BottomLeft,
+                        color: layer_color,
+                        ..Default::default()
+                    },
+                    Transform::from_translation(cell_corner.extend(0.0)),
+                    ChildOf(tilemap_entity),
+                ));
+                if let Some(animation) = tile_animation(tiled_map, tileset_index, layer_tile.id()) {
+                    tile_entity.insert(animation);
                 }
+                insert_tile_classes(
+                    map.class_registry,
+                    &mut tile_entity,
+                    map.path,
+                    tileset,
+                    layer_tile.id(),
+                );
+                continue;
             }
+
+            let texture_index = match tilemap_texture {
+                TilemapTexture::Single(_) => layer_tile.id(),
+                TilemapTexture::Vector(_) => match tiled_map.tile_image_offsets.get(&tile_key) {
+                    Some(index) => *index,
+                    None => {
+                        warn!(
+                            "Skipping tile {} with no image in tileset {}",
+                            layer_tile.id(),
+                            tileset.name
+                        );
+                        continue;
+                    }
+                },
+                _ => {
+                    warn!(
+                        "Skipping tile {} with an unsupported texture in tileset {}",
+                        layer_tile.id(),
+                        tileset.name
+                    );
+                    continue;
+                }
+            };
+
+            let mut tile_entity = commands.spawn((
+                TileBundle {
+                    position: tile_pos,
+                    tilemap_id: TilemapId(tilemap_entity),
+                    texture_index: TileTextureIndex(texture_index),
+                    color: TileColor(layer_color),
+                    flip,
+                    ..Default::default()
+                },
+                ChildOf(tilemap_entity),
+            ));
+            if let Some(animation) = tile_animation(tiled_map, tileset_index, layer_tile.id()) {
+                tile_entity.insert(animation);
+            }
+            insert_tile_classes(
+                map.class_registry,
+                &mut tile_entity,
+                map.path,
+                tileset,
+                layer_tile.id(),
+            );
+            tile_storage.set(&tile_pos, tile_entity.id());
         }
     }
+
+    for rect in merge_solid_tiles(&solid_tiles, map_size) {
+        let tile_center = |x, y| {
+            TilePos { x, y }.center_in_world(
+                &map_size,
+                &grid_size,
+                &tile_size,
+                &map_type,
+                &TilemapAnchor::None,
+            )
+        };
+        let half_grid_size = Vec2::new(grid_size.x, grid_size.y) / 2.0;
+        let min = tile_center(rect.min.x, rect.min.y) - half_grid_size;
+        let max = tile_center(rect.max.x, rect.max.y) + half_grid_size;
+        let size = max - min;
+
+        commands.spawn((
+            TiledColliderObject,
+            RigidBody::Static,
+            Collider::rectangle(size.x, size.y),
+            Transform::from_translation(((min + max) / 2.0).extend(0.0)),
+            ChildOf(tilemap_entity),
+        ));
+    }
+
+    if !layer_colliders.is_empty() {
+        commands.spawn((
+            TiledColliderObject,
+            RigidBody::Static,
+            Collider::compound(layer_colliders),
+            Transform::default(),
+            ChildOf(tilemap_entity),
+        ));
+    }
+
+    commands.entity(tilemap_entity).insert(TilemapBundle {
+        grid_size,
+        size: map_size,
+        storage: tile_storage,
+        texture: tilemap_texture.clone(),
+        tile_size,
+        spacing: tile_spacing,
+        anchor: TilemapAnchor::None,
+        transform: Transform::from_translation(grid.layer_origin(&tile_size).extend(0.0)),
+        map_type,
+        render_settings: *map.render_settings,
+        ..Default::default()
+    });
+    Some(tilemap_entity)
+}
+
+/// Reads which axes each image layer of a map repeats along, by the id of the layer.
+///
+/// `tiled` doesn't read the `repeatx` and `repeaty` attributes of image layers, so they're read
+/// from the map's XML directly. Layers that don't repeat are left out.
+fn image_layer_repeats(map_bytes: &[u8]) -> HashMap<u32, BVec2> {
+    let mut repeats = HashMap::default();
+    for event in xml::reader::EventReader::new(map_bytes) {
+        // The map has already been parsed, so any errors were reported then.
+        let Ok(xml::reader::XmlEvent::StartElement {
+            name, attributes, ..
+        }) = event
+        else {
+            continue;
+        };
+        if name.local_name != "imagelayer" {
+            continue;
+        }
+
+        let attribute = |attribute_name: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.name.local_name == attribute_name)
+                .map(|attribute| attribute.value.as_str())
+        };
+        let Some(id) = attribute("id").and_then(|id| id.parse().ok()) else {
+            continue;
+        };
+        let repeat = BVec2::new(
+            attribute("repeatx") == Some("1"),
+            attribute("repeaty") == Some("1"),
+        );
+        if repeat.any() {
+            repeats.insert(id, repeat);
+        }
+    }
+    repeats
+}
+
+/// Spawns the sprite of an image layer, if it has an image.
+///
+/// Images that repeat are tiled across the whole map along the axes they repeat on.
+fn spawn_image_layer(
+    commands: &mut Commands,
+    tiled_map: &TiledMap,
+    map_layer: &MapLayer,
+    map_transform: &Transform,
+) -> Option<Entity> {
+    let tiled::LayerType::Image(image_layer) = map_layer.layer.layer_type() else {
+        return None;
+    };
+    let img = image_layer.image.as_ref()?;
+    let image = tiled_map.image_layers.get(&map_layer.layer.id())?;
+    let image_size = Vec2::new(img.width as f32, img.height as f32);
+    if image_size.cmple(Vec2::ZERO).any() {
+        return None;
+    }
+    let repeat = tiled_map
+        .image_layer_repeats
+        .get(&map_layer.layer.id())
+        .copied()
+        .unwrap_or(BVec2::FALSE);
+
+    // Repeated images start a whole number of images before the edge of the map, so they stay
+    // lined up with the layer's offset.
+    let grid = tiled_map.grid();
+    let map_size = grid.size();
+    let mut top_left = map_layer.offset;
+    let mut size = image_size;
+    if repeat.x {
+        top_left.x -= (top_left.x / image_size.x).ceil() * image_size.x;
+        size.x = ((map_size.x - top_left.x) / image_size.x).ceil() * image_size.x;
+    }
+    if repeat.y {
+        top_left.y -= (top_left.y / image_size.y).ceil() * image_size.y;
+        size.y = ((map_size.y - top_left.y) / image_size.y).ceil() * image_size.y;
+    }
+
+    let image_mode = if repeat.any() {
+        SpriteImageMode::Tiled {
+            tile_x: repeat.x,
+            tile_y: repeat.y,
+            stretch_value: 1.0,
+        }
+    } else {
+        SpriteImageMode::Auto
+    };
+
+    // Image layers are drawn at the same depth as other layers, which isn't scaled by the map.
+    let settings = &tiled_map.settings;
+    let z = map_layer.z / map_transform.scale.z;
+    let position = grid.screen_to_local(top_left) * settings.scale;
+
+    let entity = commands
+        .spawn((
+            TiledImageLayer {
+                id: map_layer.layer.id(),
+            },
+            Sprite {
+                image: image.clone(),
+                custom_size: Some(size),
+                color: map_layer.color(),
+                anchor: Anchor::TopLeft,
+                image_mode,
+                ..Default::default()
+            },
+            Transform::from_translation(position.extend(z)).with_scale(Vec3::splat(settings.scale)),
+            map_layer.visibility(),
+            ChildOf(map_layer.parent),
+        ))
+        .id();
+    Some(entity)
+}
+
+/// Moves layers with a parallax factor relative to the camera.
+///
+/// Runs after the camera has been moved (and clamped to the map's bounds) for the frame, so the
+/// layers never lag behind it.
+fn apply_parallax(
+    camera: Single<&Transform, (With<Camera2d>, Without<TiledParallax>)>,
+    maps: Query<(&Transform, &TiledMapHandle), Without<TiledParallax>>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    mut layers: Query<(&mut TiledParallax, &mut Transform)>,
+) {
+    for (mut parallax, mut transform) in &mut layers {
+        let Ok((map_transform, map_handle)) = maps.get(parallax.map) else {
+            continue;
+        };
+        let Some(tiled_map) = tiled_maps.get(&map_handle.0) else {
+            continue;
+        };
+
+        // Like in `Tiled`, layers are at rest when the camera is centered on the top-left corner
+        // of the map.
+        let origin = tiled_map.grid().screen_to_local(Vec2::ZERO) * tiled_map.settings.scale;
+        let origin = map_transform.transform_point(origin.extend(0.0)).truncate();
+        let offset = (camera.translation.truncate() - origin) * (Vec2::ONE - parallax.factor);
+
+        // Layers are moved in the local coordinates of the map (groups don't move them).
+        let offset = offset / map_transform.scale.truncate();
+
+        transform.translation += (offset - parallax.offset).extend(0.0);
+        parallax.offset = offset;
+    }
+}
+
+/// Keeps `SpawnPoints` in sync with the spawn points of the current map.
+fn update_spawn_points(
+    current_map: Res<CurrentMap>,
+    maps: Query<(&Name, Ref<MapSpawnPoints>)>,
+    mut spawn_points: ResMut<SpawnPoints>,
+) {
+    for (name, map_spawn_points) in &maps {
+        if *name == current_map.0 && (map_spawn_points.is_changed() || current_map.is_changed()) {
+            *spawn_points = SpawnPoints {
+                map: name.0.clone(),
+                points: map_spawn_points.0.clone(),
+            };
+        }
+    }
+}
+
+/// Keeps `MapBounds` in sync with the bounds of the current map.
+fn update_map_bounds(
+    current_map: Res<CurrentMap>,
+    maps: Query<(&Name, Ref<Bounds>)>,
+    mut map_bounds: ResMut<MapBounds>,
+) {
+    for (name, bounds) in &maps {
+        if *name == current_map.0 && (bounds.is_changed() || current_map.is_changed()) {
+            info!("Map bounds calculated: {:?}", *bounds);
+            map_bounds.0 = *bounds;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn flip_collision_shapes_moves_and_rotates_parts() {
+        // A part centered at (4, -2) in a 32x16 image, rotated by 0.5 radians.
+        let shapes = vec![(
+            Vec2::new(4.0, -2.0),
+            Rotation::radians(0.5),
+            Collider::rectangle(8.0, 4.0),
+        )];
+        let size = Vec2::new(32.0, 16.0);
+        let flip = |x, y, d| TileFlip { x, y, d };
+
+        // The flip, and the position and rotation of the part afterwards.
+        let cases = [
+            (flip(false, false, false), Vec2::new(4.0, -2.0), 0.5),
+            (flip(true, false, false), Vec2::new(28.0, -2.0), -0.5),
+            (
+                flip(false, true, false),
+                Vec2::new(4.0, -14.0),
+                std::f32::consts::PI - 0.5,
+            ),
+            (
+                flip(true, true, false),
+                Vec2::new(28.0, -14.0),
+                0.5 + std::f32::consts::PI,
+            ),
+            // Flipping diagonally swaps the axes of the image, which becomes 16x32.
+            (
+                flip(false, false, true),
+                Vec2::new(2.0, -4.0),
+                std::f32::consts::FRAC_PI_2 - 0.5,
+            ),
+            // Tiled rotates tiles clockwise by flipping them diagonally, then horizontally.
+            (
+                flip(true, false, true),
+                Vec2::new(14.0, -4.0),
+                0.5 - std::f32::consts::FRAC_PI_2,
+            ),
+            (
+                flip(false, true, true),
+                Vec2::new(2.0, -28.0),
+                0.5 + std::f32::consts::FRAC_PI_2,
+            ),
+        ];
+
+        for (flip, expected_position, expected_rotation) in cases {
+            let flipped = flip_collision_shapes(shapes.clone(), size, flip);
+            let [(position, rotation, _)] = flipped.as_slice() else {
+                panic!("expected a single part for {flip:?}");
+            };
+            assert!(
+                position.abs_diff_eq(expected_position, EPSILON),
+                "position {position} for {flip:?}, expected {expected_position}"
+            );
+            let expected_rotation = Rotation::radians(expected_rotation);
+            assert!(
+                (rotation.sin - expected_rotation.sin).abs() < EPSILON
+                    && (rotation.cos - expected_rotation.cos).abs() < EPSILON,
+                "rotation {} for {flip:?}, expected {}",
+                rotation.as_radians(),
+                expected_rotation.as_radians()
+            );
+        }
+    }
+
+    #[test]
+    fn flip_collision_shapes_mirrors_asymmetric_shapes() {
+        let shapes = vec![(
+            Vec2::ZERO,
+            Rotation::IDENTITY,
+            Collider::triangle(Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::new(0.0, 1.0)),
+        )];
+        let size = Vec2::new(16.0, 16.0);
+        let flip = |x, y, d| TileFlip { x, y, d };
+
+        // Mirrored flips mirror the triangle along its x-axis, while turning it around doesn't.
+        let cases = [
+            (flip(true, false, false), true),
+            (flip(false, true, false), true),
+            (flip(true, true, false), false),
+            (flip(false, false, true), true),
+            (flip(true, false, true), false),
+        ];
+
+        for (flip, is_mirrored) in cases {
+            let flipped = flip_collision_shapes(shapes.clone(), size, flip);
+            let triangle = flipped[0].2.shape().as_triangle().expect("a triangle");
+            // The vertices may be reordered to keep their winding.
+            let tip = Vec2::new(if is_mirrored { -2.0 } else { 2.0 }, 0.0);
+            assert!(
+                triangle
+                    .vertices()
+                    .iter()
+                    .any(|vertex| Vec2::new(vertex.x, vertex.y).distance(tip) < EPSILON),
+                "vertices {:?} for {flip:?}",
+                triangle.vertices()
+            );
+        }
+    }
+
+    #[test]
+    fn merge_solid_tiles_covers_each_tile_once() {
+        // Each map is drawn with its top row first, where `#` is a solid tile.
+        let cases: [(&[&str], &[URect]); 5] = [
+            (&["...", "..."], &[]),
+            (&["##", "##"], &[URect::new(0, 0, 1, 1)]),
+            // An L-shape is split where the bottom row can't grow upwards.
+            (
+                &["#..", "#..", "###"],
+                &[URect::new(0, 0, 2, 0), URect::new(0, 1, 0, 2)],
+            ),
+            (
+                &["##", "#."],
+                &[URect::new(0, 0, 0, 1), URect::new(1, 1, 1, 1)],
+            ),
+            (
+                &["#.#", "###"],
+                &[
+                    URect::new(0, 0, 2, 0),
+                    URect::new(0, 1, 0, 1),
+                    URect::new(2, 1, 2, 1),
+                ],
+            ),
+        ];
+
+        for (rows, expected) in cases {
+            let map_size = TilemapSize {
+                x: rows[0].len() as u32,
+                y: rows.len() as u32,
+            };
+            let solid_tiles: Vec<_> = rows
+                .iter()
+                .rev()
+                .flat_map(|row| row.chars().map(|tile| tile == '#'))
+                .collect();
+            assert_eq!(
+                merge_solid_tiles(&solid_tiles, map_size),
+                expected,
+                "{rows:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_asset_path_stays_within_the_asset_source() {
+        let map_path = AssetPath::from("maps/map_00/main.tmx");
+        let cases = [
+            (
+                "maps/map_00/tiles.tsx",
+                Some(AssetPath::from("maps/map_00/tiles.tsx")),
+            ),
+            (
+                "maps/map_00/./b.png",
+                Some(AssetPath::from("maps/map_00/b.png")),
+            ),
+            (
+                "maps/map_00/../tiles/a.tsx",
+                Some(AssetPath::from("maps/tiles/a.tsx")),
+            ),
+            ("maps/map_00/../../a.png", Some(AssetPath::from("a.png"))),
+            // `..` can't leave the root of the asset source.
+            ("maps/map_00/../../../x.png", None),
+            ("/abs.png", None),
+            (
+                "maps/map_00/embedded://tiles.png",
+                Some(AssetPath::from("embedded://tiles.png")),
+            ),
+            (
+                "maps/map_00/embedded://a/../tiles.png",
+                Some(AssetPath::from("embedded://tiles.png")),
+            ),
+            ("maps/map_00/embedded://../tiles.png", None),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(
+                resolve_asset_path(&map_path, Path::new(path)).ok(),
+                expected,
+                "{path}"
+            );
+        }
+
+        // Paths without a source stay in the map's source.
+        let map_path = AssetPath::from("embedded://maps/main.tmx");
+        assert_eq!(
+            resolve_asset_path(&map_path, Path::new("maps/../tiles.png")).ok(),
+            Some(AssetPath::from("embedded://tiles.png"))
+        );
+    }
 }
@@ -0,0 +1,239 @@
+//! Inserting components on entities created from `Tiled` classes.
+
+use std::any::TypeId;
+
+use bevy::color::Color;
+use bevy::ecs::system::EntityCommands;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::{App, Component, Resource};
+use bevy::reflect::{
+    DynamicEnum, DynamicVariant, GetTypeRegistration, PartialReflect, Reflect, ReflectMut,
+};
+use serde::de::Error;
+
+use super::properties::PropertyError;
+
+/// Inserts a component built from the custom properties of an object or tile.
+type InsertClass = fn(&mut EntityCommands, &tiled::Properties) -> Result<(), PropertyError>;
+
+/// A Rust type mirrored by a custom enum or class in the `Tiled` project.
+#[derive(Debug, Clone)]
+pub struct TiledPropertyType {
+    /// The name of the enum or class in `Tiled`.
+    pub name: String,
+
+    pub type_id: TypeId,
+
+    /// Creates the default value of the type, which sets the default value of each class member.
+    pub default: fn() -> Box<dyn PartialReflect>,
+
+    /// What a new class can be used for in `Tiled`, such as `property` or `object`.
+    pub use_as: &'static [&'static str],
+}
+
+/// The components inserted for each `Tiled` class, registered with
+/// [`TiledClassAppExt::register_tiled_class`].
+#[derive(Resource, Default)]
+pub struct TiledClassRegistry {
+    classes: HashMap<String, Vec<InsertClass>>,
+    property_types: Vec<TiledPropertyType>,
+}
+
+impl TiledClassRegistry {
+    /// The types registered for `Tiled`, in the order they were registered.
+    pub fn property_types(&self) -> &[TiledPropertyType] {
+        &self.property_types
+    }
+
+    /// Finds the name of the custom type a Rust type was registered as.
+    pub fn property_type_name(&self, type_id: TypeId) -> Option<&str> {
+        self.property_types
+            .iter()
+            .find(|property_type| property_type.type_id == type_id)
+            .map(|property_type| property_type.name.as_str())
+    }
+
+    /// Checks whether any components are registered for the given class.
+    pub fn contains(&self, class: &str) -> bool {
+        self.classes.contains_key(class)
+    }
+
+    /// Inserts the components registered for the given class, with their fields set from the
+    /// given properties.
+    pub fn insert(
+        &self,
+        entity: &mut EntityCommands,
+        class: &str,
+        properties: &tiled::Properties,
+    ) -> Result<(), PropertyError> {
+        for insert in self.classes.get(class).into_iter().flatten() {
+            insert(entity, properties)?;
+        }
+        Ok(())
+    }
+}
+
+/// Registers components to be inserted on the objects and tiles of a `Tiled` class.
+pub trait TiledClassAppExt {
+    /// Inserts `C` on every object or tile whose class is `class`.
+    ///
+    /// The component starts from its default value, then each custom property sets the field with
+    /// the same name. Properties without a matching field are ignored.
+    fn register_tiled_class<C>(&mut self, class: impl Into<String>) -> &mut Self
+    where
+        C: Component + Reflect + GetTypeRegistration + Default;
+
+    /// Registers a struct or enum used as the value of custom properties, so it's exported to the
+    /// `Tiled` project as the custom class or enum `name`.
+    fn register_tiled_property_type<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: Reflect + GetTypeRegistration + Default;
+}
+
+impl TiledClassAppExt for App {
+    fn register_tiled_class<C>(&mut self, class: impl Into<String>) -> &mut Self
+    where
+        C: Component + Reflect + GetTypeRegistration + Default,
+    {
+        let class = class.into();
+        self.register_type::<C>();
+
+        let mut registry = self
+            .world_mut()
+            .get_resource_or_init::<TiledClassRegistry>();
+        registry
+            .classes
+            .entry(class.clone())
+            .or_default()
+            .push(insert_class::<C>);
+        registry.property_types.push(TiledPropertyType {
+            name: class,
+            type_id: TypeId::of::<C>(),
+            default: default_value::<C>,
+            use_as: &["object", "tile"],
+        });
+        self
+    }
+
+    fn register_tiled_property_type<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: Reflect + GetTypeRegistration + Default,
+    {
+        self.register_type::<T>();
+        self.world_mut()
+            .get_resource_or_init::<TiledClassRegistry>()
+            .property_types
+            .push(TiledPropertyType {
+                name: name.into(),
+                type_id: TypeId::of::<T>(),
+                default: default_value::<T>,
+                use_as: &["property"],
+            });
+        self
+    }
+}
+
+fn default_value<T: Reflect + Default>() -> Box<dyn PartialReflect> {
+    Box::new(T::default())
+}
+
+fn insert_class<C: Component + Reflect + Default>(
+    entity: &mut EntityCommands,
+    properties: &tiled::Properties,
+) -> Result<(), PropertyError> {
+    let mut component = C::default();
+    apply_properties(component.as_partial_reflect_mut(), properties)?;
+    entity.insert(component);
+    Ok(())
+}
+
+/// Sets the fields of a struct from a set of properties (or the members of a class).
+fn apply_properties(
+    target: &mut dyn PartialReflect,
+    properties: &tiled::Properties,
+) -> Result<(), PropertyError> {
+    let ReflectMut::Struct(target) = target.reflect_mut() else {
+        return Err(PropertyError::custom(format!(
+            "'{}' isn't a struct",
+            target.reflect_type_path()
+        )));
+    };
+
+    for (name, value) in properties {
+        if let Some(field) = target.field_mut(name) {
+            apply_property(field, value).map_err(|e| e.in_property(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Sets a single field from a property.
+///
+/// Numbers are converted to the type of the field, strings also set enums by the name of their
+/// variant, and classes set the fields of nested structs.
+fn apply_property(
+    target: &mut dyn PartialReflect,
+    value: &tiled::PropertyValue,
+) -> Result<(), PropertyError> {
+    let is_set = match value {
+        tiled::PropertyValue::BoolValue(value) => set(target, *value),
+        tiled::PropertyValue::FloatValue(value) => set_number(target, *value as f64),
+        tiled::PropertyValue::IntValue(value) => set_number(target, *value as f64),
+        tiled::PropertyValue::ObjectValue(value) => set_number(target, *value as f64),
+        tiled::PropertyValue::ColorValue(color) => set(
+            target,
+            Color::srgba_u8(color.red, color.green, color.blue, color.alpha),
+        ),
+        tiled::PropertyValue::StringValue(value) | tiled::PropertyValue::FileValue(value) => {
+            match target.reflect_mut() {
+                ReflectMut::Enum(target) => {
+                    let variant = DynamicEnum::new(value.clone(), DynamicVariant::Unit);
+                    target.try_apply(&variant).is_ok()
+                }
+                _ => set(target, value.clone()),
+            }
+        }
+        tiled::PropertyValue::ClassValue { properties, .. } => {
+            return apply_properties(target, properties);
+        }
+    };
+
+    if is_set {
+        Ok(())
+    } else {
+        Err(PropertyError::custom(format!(
+            "can't be read into '{}'",
+            target.reflect_type_path()
+        )))
+    }
+}
+
+/// Sets the target if it has the same type as the value (or is an `Option` of it).
+fn set<T>(target: &mut dyn PartialReflect, value: T) -> bool
+where
+    T: PartialReflect + Clone,
+    Option<T>: PartialReflect,
+{
+    target.try_apply(&value).is_ok() || target.try_apply(&Some(value)).is_ok()
+}
+
+/// Sets the target if it's any numeric type (or an `Option` of one).
+fn set_number(target: &mut dyn PartialReflect, value: f64) -> bool {
+    macro_rules! set_as {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(target) = target.try_downcast_mut::<$ty>() {
+                    *target = value as $ty;
+                    return true;
+                }
+                if let Some(target) = target.try_downcast_mut::<Option<$ty>>() {
+                    *target = Some(value as $ty);
+                    return true;
+                }
+            )*
+        };
+    }
+
+    set_as!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+    false
+}
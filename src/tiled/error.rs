@@ -0,0 +1,182 @@
+//! The errors from loading a `Tiled` map.
+
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+
+use super::project::InvalidProperties;
+
+/// An error from loading a map with the `TiledLoader`.
+#[derive(Debug)]
+pub enum TiledLoaderError {
+    /// The map, or a file it depends on, couldn't be read.
+    Io(std::io::Error),
+
+    /// The map (or its project) isn't valid.
+    Parse {
+        /// The path of the file that couldn't be parsed.
+        path: PathBuf,
+
+        /// The line of the error, starting from 1, if known.
+        line: Option<u64>,
+
+        /// The element containing the error, if known.
+        element: Option<String>,
+
+        message: String,
+    },
+
+    /// An external tileset used by the map doesn't exist.
+    MissingTileset { path: PathBuf },
+
+    /// The map's custom properties don't match the property types of its project.
+    InvalidProperty(InvalidProperties),
+
+    /// A file used by the map isn't within the assets.
+    InvalidPath { path: PathBuf, reason: String },
+}
+
+impl TiledLoaderError {
+    /// Converts an error from `tiled` while loading the map at the given path, after it had read
+    /// `map_read` bytes of the map.
+    pub(super) fn from_tiled(
+        path: PathBuf,
+        map_bytes: &[u8],
+        map_read: usize,
+        error: tiled::Error,
+    ) -> Self {
+        match error {
+            tiled::Error::ResourceLoadingError { path, err } => {
+                match err.downcast::<std::io::Error>() {
+                    Ok(err) if err.kind() == ErrorKind::NotFound && is_tileset(&path) => {
+                        Self::MissingTileset { path }
+                    }
+                    Ok(err) if err.kind() == ErrorKind::InvalidInput => Self::InvalidPath {
+                        path,
+                        reason: err.to_string(),
+                    },
+                    Ok(err) => Self::Io(*err),
+                    Err(err) => Self::Io(std::io::Error::other(err)),
+                }
+            }
+
+            error @ tiled::Error::XmlDecodingError(_) => {
+                let (line, element) = locate_xml_error(map_bytes).unzip();
+                Self::Parse {
+                    path,
+                    line,
+                    element: element.flatten(),
+                    message: error.to_string(),
+                }
+            }
+
+            error => {
+                let read_bytes = &map_bytes[..map_read.min(map_bytes.len())];
+                let (line, element) = locate_last_element(read_bytes).unzip();
+                Self::Parse {
+                    path,
+                    line,
+                    element,
+                    message: error.to_string(),
+                }
+            }
+        }
+    }
+}
+
+fn is_tileset(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "tsx")
+}
+
+/// Finds the line and innermost element of the first syntax error in a map, if it has one.
+///
+/// Errors in external tilesets and templates are reported by `tiled` without the file they
+/// happened in, so only the map itself is checked.
+fn locate_xml_error(map_bytes: &[u8]) -> Option<(u64, Option<String>)> {
+    let mut elements = Vec::new();
+    for event in EventReader::new(map_bytes) {
+        match event {
+            Ok(XmlEvent::StartElement { name, .. }) => elements.push(name.local_name),
+            Ok(XmlEvent::EndElement { .. }) => {
+                elements.pop();
+            }
+            Ok(_) => {}
+            Err(error) => return Some((error.position().row + 1, elements.pop())),
+        }
+    }
+    None
+}
+
+/// Finds the line and name of the last element started in the part of a map read by `tiled`.
+///
+/// `tiled` reports invalid attributes, tiles and properties without where they are, but stops
+/// reading the map at them, so they're in (or on) the last element it started reading.
+fn locate_last_element(read_bytes: &[u8]) -> Option<(u64, String)> {
+    let mut reader = EventReader::new(read_bytes);
+    let mut element = None;
+    loop {
+        match reader.next() {
+            Ok(XmlEvent::StartElement { name, .. }) => {
+                element = Some((reader.position().row + 1, name.local_name));
+            }
+            // The read part of the map ends in the middle of the document.
+            Ok(XmlEvent::EndDocument) | Err(_) => return element,
+            Ok(_) => {}
+        }
+    }
+}
+
+impl fmt::Display for TiledLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Could not read map: {error}"),
+            Self::Parse {
+                path,
+                line,
+                element,
+                message,
+            } => {
+                write!(f, "Could not parse '{}'", path.display())?;
+                if let Some(line) = line {
+                    write!(f, " at line {line}")?;
+                }
+                if let Some(element) = element {
+                    write!(f, " in <{element}>")?;
+                }
+                write!(f, ": {message}")
+            }
+            Self::MissingTileset { path } => {
+                write!(f, "The tileset '{}' doesn't exist", path.display())
+            }
+            Self::InvalidProperty(error) => error.fmt(f),
+            Self::InvalidPath { path, reason } => {
+                write!(f, "Could not use '{}': {reason}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TiledLoaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::InvalidProperty(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TiledLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<InvalidProperties> for TiledLoaderError {
+    fn from(error: InvalidProperties) -> Self {
+        Self::InvalidProperty(error)
+    }
+}
@@ -0,0 +1,283 @@
+//! Writing the types registered for `Tiled` to the custom property types of a `Tiled` project, so
+//! the editor stays in sync with the Rust side.
+
+use std::path::Path;
+
+use bevy::color::{Color, ColorToPacked, Srgba};
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy::log::warn;
+use bevy::prelude::World;
+use bevy::reflect::std_traits::ReflectDefault;
+use bevy::reflect::{PartialReflect, ReflectRef, TypeInfo, TypeRegistry, VariantInfo};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::classes::TiledClassRegistry;
+
+/// Updates the `propertyTypes` of the `Tiled` project at the given path with every type
+/// registered with `TiledClassAppExt`.
+///
+/// Registered classes have their members replaced and registered enums have their values
+/// replaced, while the rest of their settings (such as colors) are kept. Types that aren't
+/// registered are left untouched.
+pub fn export_property_types(
+    world: &World,
+    project_path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let project_path = project_path.as_ref();
+    let mut project: Value = serde_json::from_slice(&std::fs::read(project_path)?)?;
+    let Some(project_fields) = project.as_object_mut() else {
+        return Err(format!("'{}' isn't a Tiled project", project_path.display()).into());
+    };
+
+    let registry = world.resource::<TiledClassRegistry>();
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    let exporter = Exporter {
+        registry,
+        type_registry: &type_registry,
+    };
+
+    let Value::Array(property_types) = project_fields
+        .entry("propertyTypes")
+        .or_insert_with(|| Value::Array(Vec::new()))
+    else {
+        return Err("'propertyTypes' isn't an array".into());
+    };
+
+    let mut names = Vec::<&str>::new();
+    for property_type in registry.property_types() {
+        if !names.contains(&property_type.name.as_str()) {
+            names.push(&property_type.name);
+        }
+    }
+    for name in names {
+        exporter.export(name, property_types);
+    }
+
+    // Matches the formatting of the projects saved by `Tiled`.
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    project.serialize(&mut serde_json::Serializer::with_formatter(
+        &mut bytes, formatter,
+    ))?;
+    bytes.push(b'\n');
+    std::fs::write(project_path, bytes)?;
+    Ok(())
+}
+
+struct Exporter<'a> {
+    registry: &'a TiledClassRegistry,
+    type_registry: &'a TypeRegistry,
+}
+
+impl Exporter<'_> {
+    /// Adds a registered type to the property types, or updates it if it already exists.
+    ///
+    /// Several components can be registered for the same class, so the members of every type
+    /// registered with the same name are combined.
+    fn export(&self, name: &str, property_types: &mut Vec<Value>) {
+        let mut kind = None;
+        let mut members = Vec::<Value>::new();
+        let mut values = Vec::new();
+
+        for registered in self.registry.property_types() {
+            if registered.name != name {
+                continue;
+            }
+
+            let value = (registered.default)();
+            let registered_kind = match value.reflect_ref() {
+                ReflectRef::Struct(value) => {
+                    for index in 0..value.field_len() {
+                        let (Some(field_name), Some(field)) =
+                            (value.name_at(index), value.field_at(index))
+                        else {
+                            continue;
+                        };
+                        let Some(member) = self.member(field_name, field) else {
+                            continue;
+                        };
+                        members.retain(|existing| existing["name"] != field_name);
+                        members.push(member);
+                    }
+                    "class"
+                }
+
+                ReflectRef::Enum(_) => {
+                    let Some(TypeInfo::Enum(info)) = value.get_represented_type_info() else {
+                        continue;
+                    };
+                    values = info.variant_names().to_vec();
+                    "enum"
+                }
+
+                _ => {
+                    warn!(
+                        "Skipping '{name}', since only structs and enums can be exported to Tiled."
+                    );
+                    return;
+                }
+            };
+
+            if kind.is_some_and(|kind| kind != registered_kind) {
+                warn!("Skipping '{name}', since it's registered as both a class and an enum.");
+                return;
+            }
+            kind = Some(registered_kind);
+        }
+
+        let Some(kind) = kind else {
+            return;
+        };
+        // `Tiled` sorts members by name.
+        members.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+        match property_types
+            .iter_mut()
+            .find(|existing| existing["name"] == name)
+        {
+            Some(existing) if existing["type"] == kind => {
+                if kind == "class" {
+                    existing["members"] = members.into();
+                } else {
+                    existing["values"] = values.into();
+                }
+            }
+
+            Some(existing) => warn!(
+                "Skipping '{name}', since it's a {} in Tiled but a {kind} in Rust.",
+                existing["type"]
+            ),
+
+            None => {
+                let id = property_types
+                    .iter()
+                    .filter_map(|existing| existing["id"].as_u64())
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                let use_as = self
+                    .registry
+                    .property_types()
+                    .iter()
+                    .filter(|registered| registered.name == name)
+                    .flat_map(|registered| registered.use_as.iter().copied())
+                    .collect::<Vec<_>>();
+
+                let new = if kind == "class" {
+                    json!({
+                        "id": id,
+                        "name": name,
+                        "type": kind,
+                        "members": members,
+                        "useAs": use_as,
+                    })
+                } else {
+                    json!({
+                        "id": id,
+                        "name": name,
+                        "type": kind,
+                        "values": values,
+                        "storageType": "string",
+                        "valuesAsFlags": false,
+                    })
+                };
+                property_types.push(new);
+            }
+        }
+    }
+
+    /// Creates the class member for a field, or `None` if it has no equivalent in `Tiled`.
+    fn member(&self, name: &str, value: &dyn PartialReflect) -> Option<Value> {
+        // Optional fields are set in `Tiled` by giving them a value.
+        if value.reflect_module_path() == Some("core::option")
+            && value.reflect_type_ident() == Some("Option")
+        {
+            return match value.reflect_ref() {
+                ReflectRef::Enum(option) if option.variant_name() == "Some" => {
+                    self.member(name, option.field_at(0)?)
+                }
+                _ => {
+                    let Some(TypeInfo::Enum(info)) = value.get_represented_type_info() else {
+                        return None;
+                    };
+                    let Some(VariantInfo::Tuple(some)) = info.variant("Some") else {
+                        return None;
+                    };
+                    let default = self
+                        .type_registry
+                        .get_type_data::<ReflectDefault>(some.field_at(0)?.type_id())?
+                        .default();
+                    self.member(name, default.as_partial_reflect())
+                }
+            };
+        }
+
+        let type_id = value.get_represented_type_info()?.type_id();
+        let (kind, property_type, value) =
+            if let Some(property_type) = self.registry.property_type_name(type_id) {
+                match value.reflect_ref() {
+                    ReflectRef::Enum(value) => {
+                        ("string", Some(property_type), value.variant_name().into())
+                    }
+                    _ => ("class", Some(property_type), json!({})),
+                }
+            } else if let Some(value) = value.try_downcast_ref::<bool>() {
+                ("bool", None, (*value).into())
+            } else if let Some(value) = value.try_downcast_ref::<String>() {
+                ("string", None, value.clone().into())
+            } else if let Some(value) = value.try_downcast_ref::<Color>() {
+                let [red, green, blue, alpha] = Srgba::from(*value).to_u8_array();
+                (
+                    "color",
+                    None,
+                    format!("#{alpha:02x}{red:02x}{green:02x}{blue:02x}").into(),
+                )
+            } else if let Some(value) = number(value) {
+                value
+            } else {
+                warn!(
+                    "Skipping member '{name}' of type '{}', which has no equivalent in Tiled.",
+                    value.reflect_type_path()
+                );
+                return None;
+            };
+
+        let mut member = json!({ "name": name, "type": kind, "value": value });
+        if let Some(property_type) = property_type {
+            member["propertyType"] = property_type.into();
+        }
+        Some(member)
+    }
+}
+
+/// Converts a numeric field into a `float` or `int` member.
+fn number(value: &dyn PartialReflect) -> Option<(&'static str, Option<&str>, Value)> {
+    // Whole numbers are written without a fraction, like `Tiled` does.
+    let float = |value: f64| {
+        if value.fract() == 0.0 {
+            json!(value as i64)
+        } else {
+            json!(value)
+        }
+    };
+    if let Some(value) = value.try_downcast_ref::<f32>() {
+        return Some(("float", None, float(*value as f64)));
+    }
+    if let Some(value) = value.try_downcast_ref::<f64>() {
+        return Some(("float", None, float(*value)));
+    }
+
+    macro_rules! int_as {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = value.try_downcast_ref::<$ty>() {
+                    return Some(("int", None, json!(*value)));
+                }
+            )*
+        };
+    }
+
+    int_as!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+    None
+}
@@ -0,0 +1,248 @@
+//! The layout of the tiles of a `Tiled` map, for each of its orientations.
+
+use bevy::math::{IVec2, Vec2};
+use bevy_ecs_tilemap::prelude::*;
+
+/// How the tiles of a map are laid out, used to convert between `Tiled`'s coordinates and the
+/// local coordinates of the map.
+///
+/// Local coordinates have their origin at the center of the map's bounding box, and their y-axis
+/// pointing up.
+#[derive(Debug, Clone, Copy)]
+pub struct TiledGrid {
+    orientation: tiled::Orientation,
+    stagger_axis: tiled::StaggerAxis,
+    stagger_index: tiled::StaggerIndex,
+
+    /// The length of the straight edges of hexagonal tiles, which is `0` for staggered maps.
+    side_length: f32,
+
+    /// The size of a cell of the grid in pixels.
+    tile_size: Vec2,
+
+    /// The size of the map in tiles.
+    map_size: TilemapSize,
+
+    /// The position (in `Tiled` tile coordinates) of the top-left tile of the map.
+    tile_origin: IVec2,
+}
+
+impl TiledGrid {
+    pub fn new(map: &tiled::Map, map_size: TilemapSize, tile_origin: IVec2) -> Self {
+        let side_length = match map.orientation {
+            tiled::Orientation::Hexagonal => map.hex_side_length.unwrap_or(0) as f32,
+            _ => 0.0,
+        };
+
+        Self {
+            orientation: map.orientation,
+            stagger_axis: map.stagger_axis,
+            stagger_index: map.stagger_index,
+            side_length,
+            tile_size: Vec2::new(map.tile_width as f32, map.tile_height as f32),
+            map_size,
+            tile_origin,
+        }
+    }
+
+    /// The size of a cell of the grid in pixels.
+    pub fn tile_size(&self) -> Vec2 {
+        self.tile_size
+    }
+
+    pub fn is_isometric(&self) -> bool {
+        matches!(self.orientation, tiled::Orientation::Isometric)
+    }
+
+    /// The size of the map's bounding box in pixels.
+    pub fn size(&self) -> Vec2 {
+        let tiles = Vec2::new(self.map_size.x as f32, self.map_size.y as f32);
+        match self.orientation {
+            tiled::Orientation::Orthogonal => tiles * self.tile_size,
+            tiled::Orientation::Isometric => (tiles.x + tiles.y) * self.tile_size / 2.0,
+            tiled::Orientation::Staggered | tiled::Orientation::Hexagonal => {
+                let step = self.stagger_step();
+                match self.stagger_axis {
+                    tiled::StaggerAxis::X => Vec2::new(
+                        (tiles.x - 1.0) * step.x + self.tile_size.x,
+                        (tiles.y + 0.5) * self.tile_size.y,
+                    ),
+                    tiled::StaggerAxis::Y => Vec2::new(
+                        (tiles.x + 0.5) * self.tile_size.x,
+                        (tiles.y - 1.0) * step.y + self.tile_size.y,
+                    ),
+                }
+            }
+        }
+    }
+
+    /// The distance between the centers of neighbouring columns and rows of a staggered or
+    /// hexagonal map.
+    fn stagger_step(&self) -> Vec2 {
+        match self.stagger_axis {
+            tiled::StaggerAxis::X => Vec2::new(
+                (self.tile_size.x + self.side_length) / 2.0,
+                self.tile_size.y,
+            ),
+            tiled::StaggerAxis::Y => Vec2::new(
+                self.tile_size.x,
+                (self.tile_size.y + self.side_length) / 2.0,
+            ),
+        }
+    }
+
+    /// Checks whether the given column (or row, depending on the stagger axis) is shifted by half
+    /// a tile.
+    fn is_shifted(&self, index: i32) -> bool {
+        let is_odd = index.rem_euclid(2) == 1;
+        match self.stagger_index {
+            tiled::StaggerIndex::Odd => is_odd,
+            tiled::StaggerIndex::Even => !is_odd,
+        }
+    }
+
+    /// The type of the tilemap drawing the map's layers.
+    pub fn map_type(&self) -> TilemapType {
+        match self.orientation {
+            tiled::Orientation::Orthogonal => TilemapType::Square,
+            tiled::Orientation::Isometric => TilemapType::Isometric(IsoCoordSystem::Diamond),
+
+            // Staggered maps are laid out like hexagonal maps whose tiles have no straight edges.
+            tiled::Orientation::Staggered | tiled::Orientation::Hexagonal => {
+                let is_odd = matches!(self.stagger_index, tiled::StaggerIndex::Odd);
+                match self.stagger_axis {
+                    // Odd columns are shifted up in Bevy, but down in `Tiled`.
+                    tiled::StaggerAxis::X if is_odd => {
+                        TilemapType::Hexagon(HexCoordSystem::ColumnEven)
+                    }
+                    tiled::StaggerAxis::X => TilemapType::Hexagon(HexCoordSystem::ColumnOdd),
+
+                    // Rows are counted from the bottom in Bevy, so which rows are shifted depends
+                    // on whether the map has an odd number of them.
+                    tiled::StaggerAxis::Y if is_odd == (self.map_size.y % 2 == 1) => {
+                        TilemapType::Hexagon(HexCoordSystem::RowOdd)
+                    }
+                    tiled::StaggerAxis::Y => TilemapType::Hexagon(HexCoordSystem::RowEven),
+                }
+            }
+        }
+    }
+
+    /// The grid size of the tilemap drawing the map's layers.
+    pub fn grid_size(&self) -> TilemapGridSize {
+        match self.orientation {
+            // Bevy places neighbouring rows (or columns) of hexagons three quarters of a tile
+            // apart, so the grid is stretched to match the length of the tiles' straight edges.
+            tiled::Orientation::Staggered | tiled::Orientation::Hexagonal => {
+                let step = self.stagger_step();
+                match self.stagger_axis {
+                    tiled::StaggerAxis::X => TilemapGridSize {
+                        x: step.x / 0.75,
+                        y: self.tile_size.y,
+                    },
+                    tiled::StaggerAxis::Y => TilemapGridSize {
+                        x: self.tile_size.x,
+                        y: step.y / 0.75,
+                    },
+                }
+            }
+            _ => TilemapGridSize {
+                x: self.tile_size.x,
+                y: self.tile_size.y,
+            },
+        }
+    }
+
+    /// The position in the tilemap of the tile at the given `Tiled` tile coordinates, relative to
+    /// the top-left tile of the map.
+    pub fn tile_pos(&self, x: u32, y: u32) -> TilePos {
+        // Tiled's y-axis points down, while Bevy's points up.
+        TilePos {
+            x,
+            y: self.map_size.y - 1 - y,
+        }
+    }
+
+    /// The local position of the center of the tile at the given `Tiled` tile coordinates,
+    /// relative to the top-left tile of the map.
+    pub fn tile_center(&self, x: i32, y: i32) -> Vec2 {
+        let (x, y, tile_size) = (x as f32, y as f32, self.tile_size);
+        let screen_position = match self.orientation {
+            tiled::Orientation::Orthogonal => (Vec2::new(x, y) + 0.5) * tile_size,
+            tiled::Orientation::Isometric => Vec2::new(
+                (x - y + self.map_size.y as f32) * tile_size.x / 2.0,
+                (x + y + 1.0) * tile_size.y / 2.0,
+            ),
+            tiled::Orientation::Staggered | tiled::Orientation::Hexagonal => {
+                let step = self.stagger_step();
+                let center = Vec2::new(x, y) * step + tile_size / 2.0;
+                match self.stagger_axis {
+                    tiled::StaggerAxis::X if self.is_shifted(x as i32 + self.tile_origin.x) => {
+                        center + Vec2::new(0.0, tile_size.y / 2.0)
+                    }
+                    tiled::StaggerAxis::Y if self.is_shifted(y as i32 + self.tile_origin.y) => {
+                        center + Vec2::new(tile_size.x / 2.0, 0.0)
+                    }
+                    _ => center,
+                }
+            }
+        };
+        self.screen_to_local(screen_position)
+    }
+
+    /// Converts the position of an object in `Tiled` pixel coordinates into local coordinates.
+    pub fn pixel_to_local(&self, x: f32, y: f32) -> Vec2 {
+        let origin = self.tile_origin.as_vec2();
+        let screen_position = match self.orientation {
+            tiled::Orientation::Orthogonal => Vec2::new(x, y) - origin * self.tile_size,
+
+            // Objects on isometric maps are placed in tile coordinates multiplied by the tile
+            // height, which are then projected onto the grid.
+            tiled::Orientation::Isometric => {
+                let tile = Vec2::new(x, y) / self.tile_size.y - origin;
+                Vec2::new(
+                    (tile.x - tile.y + self.map_size.y as f32) * self.tile_size.x / 2.0,
+                    (tile.x + tile.y) * self.tile_size.y / 2.0,
+                )
+            }
+
+            tiled::Orientation::Staggered | tiled::Orientation::Hexagonal => {
+                Vec2::new(x, y) - origin * self.stagger_step()
+            }
+        };
+        self.screen_to_local(screen_position)
+    }
+
+    /// Converts an offset from an object's origin in `Tiled` pixels (such as a point of its shape)
+    /// into local coordinates.
+    pub fn object_offset(&self, offset: Vec2) -> Vec2 {
+        if !self.is_isometric() {
+            return Vec2::new(offset.x, -offset.y);
+        }
+
+        let tile = offset / self.tile_size.y;
+        Vec2::new(
+            (tile.x - tile.y) * self.tile_size.x / 2.0,
+            -(tile.x + tile.y) * self.tile_size.y / 2.0,
+        )
+    }
+
+    /// The local position of a layer's tilemap, so that its tiles line up with `tile_center`.
+    pub fn layer_origin(&self, tile_size: &TilemapTileSize) -> Vec2 {
+        let first_tile = TilePos { x: 0, y: 0 }.center_in_world(
+            &self.map_size,
+            &self.grid_size(),
+            tile_size,
+            &self.map_type(),
+            &TilemapAnchor::None,
+        );
+        self.tile_center(0, self.map_size.y as i32 - 1) - first_tile
+    }
+
+    /// Converts a position relative to the top-left corner of the map's bounding box, with the
+    /// y-axis pointing down, into local coordinates.
+    pub fn screen_to_local(&self, position: Vec2) -> Vec2 {
+        let size = self.size();
+        Vec2::new(position.x - size.x / 2.0, size.y / 2.0 - position.y)
+    }
+}
@@ -0,0 +1,539 @@
+//! Loading `Tiled` projects, and validating the custom properties of maps against the property
+//! types they declare.
+
+use std::fmt;
+
+use bevy::asset::{io::Reader, Asset, AssetLoader, LoadContext};
+use bevy::reflect::TypePath;
+use serde::Deserialize;
+use xml::reader::{EventReader, XmlEvent};
+
+/// A `Tiled` project (`.tiled-project`), loaded by the `TiledProjectLoader`.
+///
+/// Only the custom property types are read, since the rest of the project is editor settings.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TiledProject {
+    #[serde(default)]
+    pub property_types: Vec<PropertyType>,
+}
+
+/// A custom enum or class declared in a `Tiled` project.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+pub enum PropertyType {
+    Enum {
+        id: u32,
+        name: String,
+        #[serde(default)]
+        storage_type: EnumStorageType,
+        values: Vec<String>,
+
+        /// Whether any number of values can be set at once.
+        #[serde(default)]
+        values_as_flags: bool,
+    },
+    Class {
+        id: u32,
+        name: String,
+        #[serde(default)]
+        members: Vec<ClassMember>,
+
+        /// What the class can be used for, such as `property` or `object`.
+        #[serde(default)]
+        use_as: Vec<String>,
+    },
+}
+
+impl PropertyType {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Enum { name, .. } | Self::Class { name, .. } => name,
+        }
+    }
+}
+
+/// How the values of a custom enum are stored in maps.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnumStorageType {
+    /// By the name of the value, separated by commas for flags.
+    #[default]
+    String,
+
+    /// By the index of the value, or a bitmask of the indices for flags.
+    Int,
+}
+
+/// A member of a custom class.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassMember {
+    pub name: String,
+
+    /// The type of the member, such as `float` or `class`.
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// The custom enum or class of the member, if it has one.
+    #[serde(default)]
+    pub property_type: Option<String>,
+
+    /// The default value of the member.
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+/// A custom property that doesn't match the property types declared in the `Tiled` project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyTypeError {
+    /// What the property belongs to, such as `object 26`.
+    pub owner: String,
+
+    /// The name of the property, with class members separated by `.`.
+    pub property: String,
+
+    pub kind: PropertyTypeErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyTypeErrorKind {
+    /// The property has a different type than the one declared for it.
+    WrongType { expected: String, found: String },
+
+    /// The property uses a custom type that the project doesn't declare.
+    UnknownType(String),
+
+    /// The property isn't one of the values of its custom enum.
+    UnknownEnumValue {
+        property_type: String,
+        value: String,
+    },
+
+    /// The property is a member that its custom class doesn't declare.
+    UnknownMember { class: String, member: String },
+}
+
+impl fmt::Display for PropertyTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Property '{}' on {}: ", self.property, self.owner)?;
+        match &self.kind {
+            PropertyTypeErrorKind::WrongType { expected, found } => {
+                write!(f, "expected a '{expected}' but found a '{found}'")
+            }
+            PropertyTypeErrorKind::UnknownType(property_type) => {
+                write!(
+                    f,
+                    "the type '{property_type}' isn't declared in the project"
+                )
+            }
+            PropertyTypeErrorKind::UnknownEnumValue {
+                property_type,
+                value,
+            } => write!(f, "'{value}' isn't a value of the enum '{property_type}'"),
+            PropertyTypeErrorKind::UnknownMember { class, member } => {
+                write!(f, "the class '{class}' has no member '{member}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PropertyTypeError {}
+
+/// The custom properties of a map that don't match its `Tiled` project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidProperties {
+    /// The path of the map.
+    pub map: String,
+
+    pub errors: Vec<PropertyTypeError>,
+}
+
+impl fmt::Display for InvalidProperties {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Map '{}' doesn't match its project's property types:",
+            self.map
+        )?;
+        for error in &self.errors {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidProperties {}
+
+/// A custom property as it's written in a map, including the custom type that `tiled` discards
+/// for everything but classes.
+#[derive(Debug, Default)]
+struct RawProperty {
+    name: String,
+    kind: String,
+    property_type: Option<String>,
+    value: String,
+    members: Vec<RawProperty>,
+}
+
+impl TiledProject {
+    /// Parses the JSON of a `.tiled-project` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// Finds the custom type with the given name.
+    pub fn property_type(&self, name: &str) -> Option<&PropertyType> {
+        self.property_types
+            .iter()
+            .find(|property_type| property_type.name() == name)
+    }
+
+    /// Checks the custom properties of every map, tileset, layer, object and tile in the given
+    /// `.tmx` (or `.tsx`) file against the declared property types.
+    pub fn validate(&self, xml: &[u8]) -> Result<Vec<PropertyTypeError>, xml::reader::Error> {
+        let mut errors = Vec::new();
+        let mut owners = Vec::new();
+        let mut properties = Vec::<RawProperty>::new();
+
+        for event in EventReader::new(xml) {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let attribute = |attribute_name: &str| {
+                        attributes
+                            .iter()
+                            .find(|attribute| attribute.name.local_name == attribute_name)
+                            .map(|attribute| attribute.value.clone())
+                    };
+
+                    match name.local_name.as_str() {
+                        "property" => properties.push(RawProperty {
+                            name: attribute("name").unwrap_or_default(),
+                            kind: attribute("type").unwrap_or_else(|| "string".into()),
+                            property_type: attribute("propertytype"),
+                            value: attribute("value").unwrap_or_default(),
+                            members: Vec::new(),
+                        }),
+                        "map" => owners.push("the map".to_string()),
+                        element
+                        @ ("tileset" | "layer" | "objectgroup" | "imagelayer" | "group") => owners
+                            .push(format!(
+                                "{element} '{}'",
+                                attribute("name").unwrap_or_default()
+                            )),
+                        element @ ("object" | "tile") => owners
+                            .push(format!("{element} {}", attribute("id").unwrap_or_default())),
+                        _ => {}
+                    }
+                }
+
+                XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                    "property" => {
+                        let Some(property) = properties.pop() else {
+                            continue;
+                        };
+                        match properties.last_mut() {
+                            // Class members are checked along with their class.
+                            Some(class) => class.members.push(property),
+                            None => {
+                                let owner = owners.last().map_or("the map", String::as_str);
+                                self.validate_property(
+                                    &property,
+                                    owner,
+                                    &property.name,
+                                    &mut errors,
+                                );
+                            }
+                        }
+                    }
+                    "map" | "tileset" | "layer" | "objectgroup" | "imagelayer" | "group"
+                    | "object" | "tile" => {
+                        owners.pop();
+                    }
+                    _ => {}
+                },
+
+                _ => {}
+            }
+        }
+
+        Ok(errors)
+    }
+
+    /// Checks a property (and the members of a class) against its custom type.
+    fn validate_property(
+        &self,
+        property: &RawProperty,
+        owner: &str,
+        path: &str,
+        errors: &mut Vec<PropertyTypeError>,
+    ) {
+        let mut error = |kind| {
+            errors.push(PropertyTypeError {
+                owner: owner.to_string(),
+                property: path.to_string(),
+                kind,
+            })
+        };
+
+        let Some(type_name) = &property.property_type else {
+            return;
+        };
+        let Some(property_type) = self.property_type(type_name) else {
+            error(PropertyTypeErrorKind::UnknownType(type_name.clone()));
+            return;
+        };
+
+        match property_type {
+            PropertyType::Enum {
+                storage_type,
+                values,
+                values_as_flags,
+                ..
+            } => {
+                let expected = match storage_type {
+                    EnumStorageType::String => "string",
+                    EnumStorageType::Int => "int",
+                };
+                if property.kind != expected {
+                    error(PropertyTypeErrorKind::WrongType {
+                        expected: expected.into(),
+                        found: property.kind.clone(),
+                    });
+                    return;
+                }
+
+                let is_valid = match (storage_type, values_as_flags) {
+                    (EnumStorageType::String, false) => values.contains(&property.value),
+                    (EnumStorageType::String, true) => property
+                        .value
+                        .split(',')
+                        .filter(|flag| !flag.is_empty())
+                        .all(|flag| values.iter().any(|value| value == flag)),
+                    (EnumStorageType::Int, false) => property
+                        .value
+                        .parse::<usize>()
+                        .is_ok_and(|index| index < values.len()),
+                    (EnumStorageType::Int, true) => property
+                        .value
+                        .parse::<u64>()
+                        .is_ok_and(|flags| values.len() >= 64 || flags >> values.len() == 0),
+                };
+                if !is_valid {
+                    error(PropertyTypeErrorKind::UnknownEnumValue {
+                        property_type: type_name.clone(),
+                        value: property.value.clone(),
+                    });
+                }
+            }
+
+            PropertyType::Class { name, members, .. } => {
+                if property.kind != "class" {
+                    error(PropertyTypeErrorKind::WrongType {
+                        expected: "class".into(),
+                        found: property.kind.clone(),
+                    });
+                    return;
+                }
+
+                for member in &property.members {
+                    let member_path = format!("{path}.{}", member.name);
+                    let mut member_error = |kind| {
+                        errors.push(PropertyTypeError {
+                            owner: owner.to_string(),
+                            property: member_path.clone(),
+                            kind,
+                        })
+                    };
+
+                    let Some(declared) =
+                        members.iter().find(|declared| declared.name == member.name)
+                    else {
+                        member_error(PropertyTypeErrorKind::UnknownMember {
+                            class: name.clone(),
+                            member: member.name.clone(),
+                        });
+                        continue;
+                    };
+
+                    // Enum members are stored as strings or ints, so their custom type is what
+                    // matters.
+                    let is_enum = declared
+                        .property_type
+                        .as_deref()
+                        .and_then(|type_name| self.property_type(type_name))
+                        .is_some_and(|declared| matches!(declared, PropertyType::Enum { .. }));
+                    if declared.property_type != member.property_type
+                        || (!is_enum && declared.kind != member.kind)
+                    {
+                        member_error(PropertyTypeErrorKind::WrongType {
+                            expected: declared
+                                .property_type
+                                .clone()
+                                .unwrap_or_else(|| declared.kind.clone()),
+                            found: member
+                                .property_type
+                                .clone()
+                                .unwrap_or_else(|| member.kind.clone()),
+                        });
+                        continue;
+                    }
+
+                    self.validate_property(member, owner, &member_path, errors);
+                }
+            }
+        }
+    }
+}
+
+/// Loads a `Tiled` project (any file with the `.tiled-project` extension).
+pub struct TiledProjectLoader;
+
+impl AssetLoader for TiledProjectLoader {
+    type Asset = TiledProject;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(TiledProject::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["tiled-project"];
+        EXTENSIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r#"{
+        "propertyTypes": [
+            { "id": 1, "name": "Body", "type": "enum", "storageType": "string",
+              "values": ["Dynamic", "Static"], "valuesAsFlags": false },
+            { "id": 2, "name": "Index", "type": "enum", "storageType": "int",
+              "values": ["First", "Second"], "valuesAsFlags": false },
+            { "id": 3, "name": "Layers", "type": "enum", "storageType": "int",
+              "values": ["Ground", "Walls", "Roof"], "valuesAsFlags": true },
+            { "id": 4, "name": "Hitbox", "type": "class", "members": [
+                { "name": "width", "type": "float", "value": 0 },
+                { "name": "height", "type": "float", "value": 0 }
+            ] }
+        ]
+    }"#;
+
+    fn wrong_type(expected: &str, found: &str) -> PropertyTypeErrorKind {
+        PropertyTypeErrorKind::WrongType {
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    fn unknown_value(property_type: &str, value: &str) -> PropertyTypeErrorKind {
+        PropertyTypeErrorKind::UnknownEnumValue {
+            property_type: property_type.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn validate_reports_mismatched_properties() {
+        let project = TiledProject::from_bytes(PROJECT.as_bytes()).unwrap();
+
+        // The properties of a map, and the name and kind of each error they should have.
+        let cases = [
+            (
+                r#"<property name="p" propertytype="Body" value="Static"/>"#,
+                vec![],
+            ),
+            (
+                r#"<property name="p" type="int" propertytype="Body" value="1"/>"#,
+                vec![("p", wrong_type("string", "int"))],
+            ),
+            (
+                r#"<property name="p" propertytype="Body" value="Flying"/>"#,
+                vec![("p", unknown_value("Body", "Flying"))],
+            ),
+            (
+                r#"<property name="p" propertytype="Unknown" value="1"/>"#,
+                vec![("p", PropertyTypeErrorKind::UnknownType("Unknown".into()))],
+            ),
+            // Int enums are stored by the index of their value.
+            (
+                r#"<property name="p" type="int" propertytype="Index" value="1"/>"#,
+                vec![],
+            ),
+            (
+                r#"<property name="p" type="int" propertytype="Index" value="2"/>"#,
+                vec![("p", unknown_value("Index", "2"))],
+            ),
+            (
+                r#"<property name="p" propertytype="Index" value="First"/>"#,
+                vec![("p", wrong_type("int", "string"))],
+            ),
+            // Int flags are stored as a bitmask of the indices of their values.
+            (
+                r#"<property name="p" type="int" propertytype="Layers" value="5"/>"#,
+                vec![],
+            ),
+            (
+                r#"<property name="p" type="int" propertytype="Layers" value="8"/>"#,
+                vec![("p", unknown_value("Layers", "8"))],
+            ),
+            (
+                r#"<property name="p" type="class" propertytype="Hitbox">
+                    <properties>
+                        <property name="width" type="float" value="2"/>
+                        <property name="depth" type="float" value="1"/>
+                        <property name="height" type="int" value="1"/>
+                    </properties>
+                </property>"#,
+                vec![
+                    (
+                        "p.depth",
+                        PropertyTypeErrorKind::UnknownMember {
+                            class: "Hitbox".into(),
+                            member: "depth".into(),
+                        },
+                    ),
+                    ("p.height", wrong_type("float", "int")),
+                ],
+            ),
+            (
+                r#"<property name="p" propertytype="Hitbox" value=""/>"#,
+                vec![("p", wrong_type("class", "string"))],
+            ),
+        ];
+
+        for (properties, expected) in cases {
+            let map = format!("<map><properties>{properties}</properties></map>");
+            let expected: Vec<_> = expected
+                .into_iter()
+                .map(|(property, kind)| PropertyTypeError {
+                    owner: "the map".into(),
+                    property: property.into(),
+                    kind,
+                })
+                .collect();
+            assert_eq!(
+                project.validate(map.as_bytes()).unwrap(),
+                expected,
+                "{properties}"
+            );
+        }
+    }
+}
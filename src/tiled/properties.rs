@@ -0,0 +1,278 @@
+//! Reading `Tiled` custom properties into Rust types.
+
+use std::fmt;
+
+use avian2d::prelude::RigidBody;
+use bevy::reflect::Reflect;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Serialize};
+
+/// An error from reading a `Tiled` custom property.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertyError {
+    /// The path of the map the property was read from, if known.
+    pub map: Option<String>,
+
+    /// The id of the object the property belongs to, if known.
+    pub object: Option<u32>,
+
+    /// The name of the layer the property belongs to, if known.
+    pub layer: Option<String>,
+
+    /// The name of the invalid property, with class members separated by `.`.
+    pub property: String,
+
+    pub message: String,
+}
+
+impl PropertyError {
+    /// Records the object the property belongs to.
+    pub fn on_object(mut self, object: u32) -> Self {
+        self.object = Some(object);
+        self
+    }
+
+    /// Records the layer the property belongs to.
+    pub fn on_layer(mut self, layer: impl Into<String>) -> Self {
+        self.layer = Some(layer.into());
+        self
+    }
+
+    /// Records the map the property was read from.
+    pub fn in_map(mut self, map: impl Into<String>) -> Self {
+        self.map = Some(map.into());
+        self
+    }
+
+    /// Records the name of the property (or class member) containing the invalid value.
+    pub(super) fn in_property(mut self, name: &str) -> Self {
+        self.property = if self.property.is_empty() {
+            name.into()
+        } else {
+            format!("{name}.{}", self.property)
+        };
+        self
+    }
+}
+
+impl fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid property")?;
+        if !self.property.is_empty() {
+            write!(f, " '{}'", self.property)?;
+        }
+        if let Some(object) = self.object {
+            write!(f, " on object {object}")?;
+        }
+        if let Some(layer) = &self.layer {
+            write!(f, " on layer '{layer}'")?;
+        }
+        if let Some(map) = &self.map {
+            write!(f, " in map '{map}'")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for PropertyError {}
+
+impl de::Error for PropertyError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Types that can be read from `Tiled` custom properties.
+///
+/// This is implemented for every type implementing `serde::Deserialize`: classes are read as
+/// structs (members left at their default value are missing), and enums are read from the names
+/// of their variants.
+pub trait FromTiledProperties: Sized {
+    /// Reads the properties of a map, layer, object or tile (or the members of a class).
+    fn from_tiled_properties(properties: &tiled::Properties) -> Result<Self, PropertyError>;
+
+    /// Reads a single property.
+    fn from_tiled_property(value: &tiled::PropertyValue) -> Result<Self, PropertyError>;
+}
+
+impl<T: DeserializeOwned> FromTiledProperties for T {
+    fn from_tiled_properties(properties: &tiled::Properties) -> Result<Self, PropertyError> {
+        T::deserialize(PropertiesDeserializer(properties))
+    }
+
+    fn from_tiled_property(value: &tiled::PropertyValue) -> Result<Self, PropertyError> {
+        T::deserialize(PropertyValueDeserializer(value))
+    }
+}
+
+/// Reads the property with the given name, if it exists.
+pub fn read_property<T: FromTiledProperties>(
+    properties: &tiled::Properties,
+    name: &str,
+) -> Result<Option<T>, PropertyError> {
+    properties
+        .get(name)
+        .map(|value| T::from_tiled_property(value).map_err(|e| e.in_property(name)))
+        .transpose()
+}
+
+/// The `RigidBody` enum from the `Tiled` project.
+#[derive(Deserialize, Serialize, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TiledRigidBody {
+    Dynamic,
+    #[default]
+    Static,
+    Kinematic,
+}
+
+impl From<TiledRigidBody> for RigidBody {
+    fn from(rigid_body: TiledRigidBody) -> Self {
+        match rigid_body {
+            TiledRigidBody::Dynamic => RigidBody::Dynamic,
+            TiledRigidBody::Static => RigidBody::Static,
+            TiledRigidBody::Kinematic => RigidBody::Kinematic,
+        }
+    }
+}
+
+/// The `Hitbox` class from the `Tiled` project.
+#[derive(Deserialize, Reflect, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Hitbox {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+}
+
+impl Hitbox {
+    /// The size of the hitbox.
+    ///
+    /// If only one of the `width` or `height` members is set, the hitbox is square.
+    pub fn size(&self) -> Option<(f32, f32)> {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => Some((width, height)),
+            (Some(size), None) | (None, Some(size)) => Some((size, size)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Deserializes a set of properties (or the members of a class) as a map.
+struct PropertiesDeserializer<'a>(&'a tiled::Properties);
+
+impl<'de> Deserializer<'de> for PropertiesDeserializer<'_> {
+    type Error = PropertyError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(PropertiesAccess {
+            properties: self.0.iter(),
+            value: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+struct PropertiesAccess<'a> {
+    properties: std::collections::hash_map::Iter<'a, String, tiled::PropertyValue>,
+    value: Option<(&'a str, &'a tiled::PropertyValue)>,
+}
+
+impl<'de> MapAccess<'de> for PropertiesAccess<'_> {
+    type Error = PropertyError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((name, value)) = self.properties.next() else {
+            return Ok(None);
+        };
+        self.value = Some((name, value));
+        seed.deserialize(de::value::StrDeserializer::<PropertyError>::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (name, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before its name"))?;
+        seed.deserialize(PropertyValueDeserializer(value))
+            .map_err(|e| e.in_property(name))
+    }
+}
+
+/// Deserializes a single property.
+struct PropertyValueDeserializer<'a>(&'a tiled::PropertyValue);
+
+impl<'de> Deserializer<'de> for PropertyValueDeserializer<'_> {
+    type Error = PropertyError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            tiled::PropertyValue::BoolValue(value) => visitor.visit_bool(*value),
+            tiled::PropertyValue::FloatValue(value) => visitor.visit_f32(*value),
+            tiled::PropertyValue::IntValue(value) => visitor.visit_i32(*value),
+            tiled::PropertyValue::ColorValue(color) => {
+                visitor.visit_map(de::value::MapDeserializer::<_, PropertyError>::new(
+                    [
+                        ("red", color.red),
+                        ("green", color.green),
+                        ("blue", color.blue),
+                        ("alpha", color.alpha),
+                    ]
+                    .into_iter(),
+                ))
+            }
+            tiled::PropertyValue::StringValue(value) | tiled::PropertyValue::FileValue(value) => {
+                visitor.visit_str(value)
+            }
+            tiled::PropertyValue::ObjectValue(value) => visitor.visit_u32(*value),
+            tiled::PropertyValue::ClassValue { properties, .. } => {
+                PropertiesDeserializer(properties).deserialize_any(visitor)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // Properties that aren't set are missing, rather than empty.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            tiled::PropertyValue::StringValue(value) => {
+                de::value::StrDeserializer::<PropertyError>::new(value)
+                    .deserialize_enum(name, variants, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,82 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    helper::CurrentMap, player::Player, screens::Screen, tiled::classes::TiledClassAppExt,
+};
+
+/// A region of the map that moves the player to another map when entered.
+///
+/// Created from objects with a `zone` property of the `Zone` class in `Tiled`.
+#[derive(Component, Deserialize, Reflect, Debug, Clone)]
+#[serde(default)]
+pub struct Zone {
+    /// The name of the map to move to.
+    pub name: String,
+
+    /// The name of the spawn point to place the player at in the new map.
+    pub spawn_point: String,
+}
+
+impl Default for Zone {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            spawn_point: "default".into(),
+        }
+    }
+}
+
+/// Sent when the player enters a `Zone`.
+#[derive(Event, Debug, Clone)]
+pub struct ZoneEntered {
+    /// The name of the map the player is leaving.
+    pub from: String,
+
+    /// The name of the map the player is moving to.
+    pub to: String,
+
+    /// The name of the spawn point to place the player at in the new map.
+    pub spawn_point: String,
+}
+
+/// Add the zone systems to the app.
+pub(crate) fn add_systems(app: &mut App) {
+    app.add_event::<ZoneEntered>();
+    register_property_types(app);
+    app.add_systems(Update, detect_zone_entry.run_if(in_state(Screen::Gameplay)));
+}
+
+/// Registers the types of the custom properties read for zones.
+pub fn register_property_types(app: &mut App) {
+    app.register_tiled_property_type::<Zone>("Zone");
+}
+
+/// Sends a `ZoneEntered` event when the player starts overlapping a zone.
+fn detect_zone_entry(
+    mut collisions: EventReader<CollisionStarted>,
+    player: Single<Entity, With<Player>>,
+    zones: Query<&Zone>,
+    current_map: Res<CurrentMap>,
+    mut zone_entered: EventWriter<ZoneEntered>,
+) {
+    for CollisionStarted(entity1, entity2) in collisions.read() {
+        let zone_entity = if *entity1 == *player {
+            *entity2
+        } else if *entity2 == *player {
+            *entity1
+        } else {
+            continue;
+        };
+
+        if let Ok(zone) = zones.get(zone_entity) {
+            info!("Entered zone to map: {}", zone.name);
+            zone_entered.write(ZoneEntered {
+                from: current_map.0 .0.clone(),
+                to: zone.name.clone(),
+                spawn_point: zone.spawn_point.clone(),
+            });
+        }
+    }
+}